@@ -0,0 +1,98 @@
+# Source for Vine.glb, Mushroom.glb and Bush.glb.
+#
+# Builds the models in Blender, saves each one as a .blend next to this script and exports it as a
+# .glb:
+#
+#     blender --background --python assets/plant_models.py
+#
+# Coordinates and sizes are in glTF space (+Y up), like the models the game loads.
+
+import os
+
+import bpy
+
+MODELS = {
+    "Vine": [
+        ("Stem", (0.05, 0.35, 0.02), [((0.0, 0.0, 0.0), (0.2, 6.0, 0.2))]),
+        ("Leaves", (0.2, 0.6, 0.05), [
+            ((0.22, -2.5, 0.0), (0.35, 0.12, 0.3)),
+            ((-0.22, -1.5, 0.0), (0.35, 0.12, 0.3)),
+            ((0.22, -0.5, 0.0), (0.35, 0.12, 0.3)),
+            ((-0.22, 0.5, 0.0), (0.35, 0.12, 0.3)),
+            ((0.22, 1.5, 0.0), (0.35, 0.12, 0.3)),
+            ((-0.22, 2.5, 0.0), (0.35, 0.12, 0.3)),
+        ]),
+    ],
+    "Mushroom": [
+        ("Stem", (0.9, 0.85, 0.7), [((0.0, -0.35, 0.0), (0.6, 0.9, 0.6))]),
+        ("Cap", (0.75, 0.05, 0.03), [((0.0, 0.45, 0.0), (2.4, 0.7, 2.4))]),
+        ("Spots", (1.0, 1.0, 1.0), [
+            ((0.6, 0.82, 0.3), (0.3, 0.05, 0.3)),
+            ((-0.5, 0.82, -0.4), (0.35, 0.05, 0.35)),
+            ((-0.1, 0.82, 0.7), (0.25, 0.05, 0.25)),
+        ]),
+    ],
+    "Bush": [
+        ("Foliage", (0.02, 0.2, 0.02), [
+            ((0.0, -0.5, 0.0), (2.0, 3.0, 1.6)),
+            ((0.0, 1.5, 0.0), (1.6, 1.0, 1.4)),
+        ]),
+        ("Berries", (0.6, 0.05, 0.3), [
+            ((0.6, 0.4, 0.81), (0.2, 0.2, 0.05)),
+            ((-0.4, -0.8, 0.81), (0.2, 0.2, 0.05)),
+            ((0.2, -1.4, 0.81), (0.2, 0.2, 0.05)),
+        ]),
+    ],
+}
+
+
+def to_blender(vector):
+    """glTF is +Y up, Blender is +Z up."""
+    x, y, z = vector
+    return (x, -z, y)
+
+
+def make_material(name, color):
+    material = bpy.data.materials.new(name)
+    material.use_nodes = True
+    bsdf = material.node_tree.nodes["Principled BSDF"]
+    bsdf.inputs["Base Color"].default_value = (*color, 1.0)
+    bsdf.inputs["Metallic"].default_value = 0.0
+    bsdf.inputs["Roughness"].default_value = 0.5
+    material.use_backface_culling = False
+    return material
+
+
+def build(name, parts):
+    bpy.ops.wm.read_factory_settings(use_empty=True)
+    cubes = []
+    for material_index, (material_name, color, boxes) in enumerate(parts):
+        material = make_material(material_name, color)
+        for center, size in boxes:
+            bpy.ops.mesh.primitive_cube_add(size=1.0, location=to_blender(center))
+            cube = bpy.context.active_object
+            sx, sy, sz = size
+            cube.scale = (sx, sz, sy)
+            cube.data.materials.append(material)
+            cubes.append(cube)
+    bpy.ops.object.select_all(action="DESELECT")
+    for cube in cubes:
+        cube.select_set(True)
+    bpy.context.view_layer.objects.active = cubes[0]
+    bpy.ops.object.join()
+    bpy.ops.object.transform_apply(location=False, rotation=False, scale=True)
+    model = bpy.context.active_object
+    model.name = name
+    model.data.name = name
+
+    directory = os.path.dirname(os.path.abspath(__file__))
+    bpy.ops.wm.save_as_mainfile(filepath=os.path.join(directory, f"{name}.blend"))
+    bpy.ops.export_scene.gltf(
+        filepath=os.path.join(directory, f"{name}.glb"),
+        export_format="GLB",
+        export_yup=True,
+    )
+
+
+for model_name, model_parts in MODELS.items():
+    build(model_name, model_parts)
@@ -0,0 +1,209 @@
+//! Entity churn and frame time with a few hundred bullets in flight, with and without the bullet
+//! pool:
+//!
+//!     cargo bench --bench bullets
+//!
+//! A column of shooters fires every frame at a wall that destroys bullets, so bullets keep being
+//! created and destroyed. The game runs headless with a fixed frame duration, so both runs
+//! simulate exactly the same frames and only the wall-clock time differs.
+
+use std::time::{Duration, Instant};
+
+use bevy::asset::LoadState;
+use bevy::diagnostic::Diagnostics;
+use bevy::prelude::*;
+use bevy::render::settings::WgpuSettings;
+use bevy::render::RenderPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::winit::WinitPlugin;
+use bevy_egui::EguiPlugin;
+use bevy_egui_kbgp::KbgpPlugin;
+use bevy_pkv::PkvStore;
+use bevy_rapier2d::prelude::*;
+use bevy_tnua::{TnuaPlatformerPlugin, TnuaRapier2dPlugin};
+use bevy_yoleck::prelude::*;
+use bevy_yoleck::vpeol::prelude::*;
+use gardening_gun::{
+    AppState, BulletDefinition, BulletDiagnosticsPlugin, BulletPooling, CanShoot, DestroysBullets,
+    Faction, GardeningGunGamePlugin, InnateAmmunition, ShootEvent, WeaponStats, BULLETS_ACTIVE,
+    BULLET_ENTITIES_SPAWNED,
+};
+
+/// Each shooter fires one bullet per frame.
+const SHOOTERS: usize = 5;
+const BULLET_SPEED: f32 = 20.0;
+/// With one second of flight, `60 * SHOOTERS` bullets are in the air at any given time.
+const WALL_DISTANCE: f32 = 20.0;
+const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+/// Enough for the first bullets to reach the wall, so that the pool is full.
+const WARMUP_FRAMES: usize = 120;
+const MEASURED_FRAMES: usize = 1200;
+
+fn main() {
+    println!(
+        "{:>10} {:>16} {:>14} {:>14} {:>14} {:>14}",
+        "pooling", "entities spawned", "peak bullets", "mean frame", "median frame", "p99 frame"
+    );
+    for pooling in [true, false] {
+        let report = run(pooling);
+        println!(
+            "{:>10} {:>16} {:>14} {:>14.2?} {:>14.2?} {:>14.2?}",
+            pooling,
+            report.bullet_entities_spawned,
+            report.peak_active_bullets,
+            report.mean_frame_time,
+            report.frame_time_percentile(0.5),
+            report.frame_time_percentile(0.99),
+        );
+    }
+}
+
+struct Report {
+    bullet_entities_spawned: usize,
+    peak_active_bullets: usize,
+    mean_frame_time: Duration,
+    sorted_frame_times: Vec<Duration>,
+}
+
+impl Report {
+    fn frame_time_percentile(&self, percentile: f32) -> Duration {
+        let index = (percentile * (self.sorted_frame_times.len() - 1) as f32).round() as usize;
+        self.sorted_frame_times[index]
+    }
+}
+
+/// Runs one frame of `FRAME_DURATION`, however long it actually takes.
+///
+/// `TimeUpdateStrategy::ManualDuration` adds the duration to the current time, so the clock is
+/// advanced manually instead.
+fn update(app: &mut App, clock: &mut Instant) {
+    *clock += FRAME_DURATION;
+    app.insert_resource(TimeUpdateStrategy::ManualInstant(*clock));
+    app.update();
+}
+
+fn run(pooling: bool) -> Report {
+    let mut app = create_app(pooling);
+    let mut clock = Instant::now();
+
+    // The bullet model must be loaded, or the first frames will not spawn its scene.
+    while app
+        .world
+        .resource::<AssetServer>()
+        .get_load_state("Bullet.glb")
+        != LoadState::Loaded
+    {
+        update(&mut app, &mut clock);
+    }
+    for _ in 0..WARMUP_FRAMES {
+        update(&mut app, &mut clock);
+    }
+
+    let mut bullet_entities_spawned = 0;
+    let mut peak_active_bullets = 0;
+    let mut frame_times = Vec::with_capacity(MEASURED_FRAMES);
+    for _ in 0..MEASURED_FRAMES {
+        let started_at = Instant::now();
+        update(&mut app, &mut clock);
+        frame_times.push(started_at.elapsed());
+
+        let diagnostics = app.world.resource::<Diagnostics>();
+        let measurement = |id| {
+            diagnostics
+                .get(id)
+                .and_then(|diagnostic| diagnostic.value())
+                .unwrap_or(0.0) as usize
+        };
+        bullet_entities_spawned += measurement(BULLET_ENTITIES_SPAWNED);
+        peak_active_bullets = peak_active_bullets.max(measurement(BULLETS_ACTIVE));
+    }
+
+    let mean_frame_time = frame_times.iter().sum::<Duration>() / frame_times.len() as u32;
+    frame_times.sort();
+    Report {
+        bullet_entities_spawned,
+        peak_active_bullets,
+        mean_frame_time,
+        sorted_frame_times: frame_times,
+    }
+}
+
+fn create_app(pooling: bool) -> App {
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(RenderPlugin {
+                wgpu_settings: WgpuSettings {
+                    backends: None,
+                    ..Default::default()
+                },
+            })
+            .disable::<WinitPlugin>(),
+    );
+    app.insert_resource(PkvStore::new("AeonFelis", "GardeningGun"));
+    app.add_plugin(EguiPlugin);
+    app.add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+    app.add_plugin(TnuaPlatformerPlugin);
+    app.add_plugin(TnuaRapier2dPlugin);
+    app.add_plugin(YoleckPluginForGame);
+    app.add_plugin(Vpeol3dPluginForGame);
+    app.add_plugin(KbgpPlugin);
+    app.add_plugin(GardeningGunGamePlugin {
+        is_editor: false,
+        start_at_level: None,
+    });
+    app.add_plugin(BulletDiagnosticsPlugin);
+
+    app.insert_resource(BulletPooling(pooling));
+    app.insert_resource(NextState(Some(AppState::Game)));
+    app.add_startup_system(setup_scenario);
+    app.add_system(fire_bullets);
+    app
+}
+
+#[derive(Component)]
+struct BenchmarkShooter;
+
+fn setup_scenario(mut commands: Commands) {
+    for i in 0..SHOOTERS {
+        commands.spawn((
+            BenchmarkShooter,
+            TransformBundle::from_transform(Transform::from_xyz(0.0, i as f32, 0.0)),
+            CanShoot::default(),
+            WeaponStats {
+                // Faster than the frame rate, so that the cooldown never skips a frame.
+                fire_rate: 120.0,
+                ..Default::default()
+            },
+            InnateAmmunition(BulletDefinition {
+                speed: BULLET_SPEED,
+                gravity: 0.0,
+                pierces: false,
+                count: 1,
+                spread_degrees: 0.0,
+                ricochets: 0,
+            }),
+            Faction::Player,
+        ));
+    }
+    commands.spawn((
+        TransformBundle::from_transform(Transform::from_xyz(
+            WALL_DISTANCE,
+            0.5 * SHOOTERS as f32,
+            0.0,
+        )),
+        RigidBody::Fixed,
+        Collider::cuboid(0.5, SHOOTERS as f32),
+        DestroysBullets,
+    ));
+}
+
+fn fire_bullets(query: Query<Entity, With<BenchmarkShooter>>, mut writer: EventWriter<ShootEvent>) {
+    for shooter_entity in query.iter() {
+        writer.send(ShootEvent {
+            shooter_entity,
+            direction: Vec3::X,
+            charged: false,
+        });
+    }
+}
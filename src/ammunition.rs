@@ -1,12 +1,23 @@
+use std::mem::discriminant;
+
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_egui::egui;
 use bevy_rapier2d::prelude::*;
 use bevy_yoleck::prelude::*;
 use bevy_yoleck::vpeol::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::animating::{ApplyRotationToChild, RotateAroundScaledAxis};
 use crate::editing_helpers::SnapToGrid;
-use crate::planting::{FlyingSeed, PlantType};
+use crate::floating_text::setup_text_texture;
+use crate::plant_definitions::{PlantDefinition, PlantDefinitions, SeedDefinition};
+use crate::planting::{FlyingSeed, Growing, PlantType, Planted, Withering};
+use crate::player_controls::{CycleAmmunitionEvent, DropAmmunitionEvent, HarvestEvent};
 use crate::utils::sensor_events_both_ways;
+use crate::AppState;
 
 pub struct AmmunitionPlugin;
 
@@ -14,42 +25,212 @@ impl Plugin for AmmunitionPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<PickEvent>();
         app.add_event::<UseUpShotEvent>();
+        app.add_event::<CycleAmmunitionEvent>();
         app.add_yoleck_entity_type({
             YoleckEntityType::new("PickableAmmo")
                 .with::<Vpeol3dPosition>()
                 .with::<PlantType>()
+                .with::<AmmoShots>()
+                .with::<AmmoRespawn>()
                 .insert_on_init_during_editor(|| SnapToGrid)
                 .insert_on_init(|| Pickable)
         });
+        app.init_resource::<ShotsLabels>();
+        app.add_yoleck_edit_system(edit_ammo_shots);
+        app.add_yoleck_edit_system(edit_ammo_respawn);
         app.yoleck_populate_schedule_mut()
             .add_system(populate_pickable_ammo);
-        app.add_systems((initiate_pickup, handle_carrying).chain());
+        app.add_systems((initiate_pickup, initiate_harvest, handle_carrying).chain());
         app.add_system(handle_useup);
+        app.add_system(handle_dropping);
+        app.add_system(handle_cycling);
+        app.add_system(show_selected_ammunition);
+        app.add_systems(
+            (start_pickable_regrowth, apply_pickable_regrowth).in_set(OnUpdate(AppState::Game)),
+        );
+    }
+}
+
+/// How many shots a pickup gives. `None` means the plant type's default.
+#[derive(YoleckComponent, Default, Clone, PartialEq, Component, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AmmoShots(pub Option<usize>);
+
+fn edit_ammo_shots(
+    mut ui: ResMut<YoleckUi>,
+    mut edit: YoleckEdit<(&mut AmmoShots, &PlantType)>,
+    plant_definitions: Res<PlantDefinitions>,
+) {
+    let Ok((mut shots, plant_type)) = edit.get_single_mut() else { return };
+    ui.horizontal(|ui| {
+        let mut is_custom = shots.0.is_some();
+        ui.checkbox(&mut is_custom, "Custom Shots");
+        if is_custom {
+            let mut value = shots.0.unwrap_or(plant_definitions.get(plant_type).shots);
+            ui.add(egui::DragValue::new(&mut value).clamp_range(1..=99));
+            shots.0 = Some(value);
+        } else {
+            shots.0 = None;
+        }
+    });
+}
+
+/// Whether a pickup comes back after it was picked.
+#[derive(YoleckComponent, Default, Clone, PartialEq, Component, Serialize, Deserialize)]
+pub enum AmmoRespawn {
+    #[default]
+    Never,
+    /// The pickup regrows this many seconds after it was picked.
+    AfterSeconds(f32),
+    /// The pickup regrows once the last seed picked from it was used.
+    WhenUsedUp,
+}
+
+fn edit_ammo_respawn(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut AmmoRespawn>) {
+    let Ok(mut respawn) = edit.get_single_mut() else { return };
+    ui.label("Respawn:");
+    ui.horizontal(|ui| {
+        for (label, option) in [
+            ("Never", AmmoRespawn::Never),
+            ("After", AmmoRespawn::AfterSeconds(10.0)),
+            ("When Used Up", AmmoRespawn::WhenUsedUp),
+        ] {
+            let selected = discriminant(respawn.as_ref()) == discriminant(&option);
+            if ui.selectable_label(selected, label).clicked() && !selected {
+                *respawn = option;
+            }
+        }
+    });
+    if let AmmoRespawn::AfterSeconds(seconds) = respawn.as_mut() {
+        ui.add(egui::Slider::new(seconds, 1.0..=120.0).suffix(" seconds"));
+    }
+}
+
+/// The plant type the pickable's model was populated with, to replace it when edited.
+#[derive(Component)]
+struct PopulatedModel(PlantType);
+
+const PICKABLE_MODEL_SCALE: f32 = 0.4;
+
+/// Marks the rotating model of the pickable, which gets scaled when it regrows.
+#[derive(Component)]
+struct PickableModel;
+
+/// The label that shows how many shots the pickable gives.
+#[derive(Component)]
+struct ShotsLabel(Entity);
+
+/// Each label texture needs its own camera and render pass, so pickups with the same number of
+/// shots share one.
+#[derive(Resource)]
+struct ShotsLabels {
+    mesh: Handle<Mesh>,
+    materials: HashMap<usize, Handle<StandardMaterial>>,
+}
+
+impl FromWorld for ShotsLabels {
+    fn from_world(world: &mut World) -> Self {
+        let mut mesh_assets = world.resource_mut::<Assets<Mesh>>();
+        Self {
+            mesh: mesh_assets.add(Mesh::from(shape::Quad::new(Vec2::new(0.8, 0.8)))),
+            materials: Default::default(),
+        }
+    }
+}
+
+impl ShotsLabels {
+    fn material(
+        &mut self,
+        shots: usize,
+        commands: &mut Commands,
+        asset_server: &AssetServer,
+        image_assets: &mut Assets<Image>,
+        material_assets: &mut Assets<StandardMaterial>,
+    ) -> Handle<StandardMaterial> {
+        self.materials
+            .entry(shots)
+            .or_insert_with(|| {
+                let mut cmd = commands.spawn(SpatialBundle::default());
+                let (text_entity, material) = setup_text_texture(
+                    &mut cmd,
+                    UVec2::new(256, 256),
+                    ClearColorConfig::Custom(Color::NONE),
+                    image_assets,
+                    material_assets,
+                );
+                commands.entity(text_entity).insert(Text::from_section(
+                    shots.to_string(),
+                    TextStyle {
+                        font: asset_server.load("FiraSans-Bold.ttf"),
+                        font_size: 200.0,
+                        color: Color::WHITE,
+                    },
+                ));
+                material
+            })
+            .clone()
     }
 }
 
 fn populate_pickable_ammo(
-    mut populate: YoleckPopulate<&PlantType>,
+    mut populate: YoleckPopulate<(
+        &PlantType,
+        &AmmoShots,
+        Option<&PopulatedModel>,
+        Option<&ShotsLabel>,
+    )>,
     asset_server: Res<AssetServer>,
+    plant_definitions: Res<PlantDefinitions>,
     marking: YoleckMarking,
+    mut shots_labels: ResMut<ShotsLabels>,
+    mut image_assets: ResMut<Assets<Image>>,
+    mut material_assets: ResMut<Assets<StandardMaterial>>,
 ) {
-    populate.populate(|ctx, mut cmd, plant_type| {
-        if ctx.is_first_time() {
-            cmd.insert(VpeolWillContainClickableChildren);
-            cmd.insert(VisibilityBundle::default());
+    populate.populate(|ctx, mut cmd, (plant_type, shots, model, label)| {
+        let definition = plant_definitions.get(plant_type);
+        if model.is_none_or(|PopulatedModel(populated)| populated != plant_type) {
+            marking.despawn_marked(&mut cmd);
             cmd.with_children(|commands| {
                 let mut child = commands.spawn(marking.marker());
                 child.insert(SceneBundle {
-                    scene: asset_server.load(plant_type.scene_name()),
+                    scene: asset_server.load(definition.scene.as_str()),
                     transform: Transform {
                         translation: Default::default(),
                         rotation: Quat::from_rotation_x(0.5),
-                        scale: Vec3::ONE * 0.4,
+                        scale: Vec3::ONE * PICKABLE_MODEL_SCALE,
                     },
                     ..Default::default()
                 });
                 child.insert(RotateAroundScaledAxis(2.0 * Vec3::Y));
+                child.insert(PickableModel);
             });
+            cmd.insert(PopulatedModel(plant_type.clone()));
+        }
+        let label_material = shots_labels.material(
+            shots.0.unwrap_or(definition.shots),
+            cmd.commands(),
+            &asset_server,
+            &mut image_assets,
+            &mut material_assets,
+        );
+        if let Some(ShotsLabel(label_entity)) = label {
+            cmd.commands().entity(*label_entity).insert(label_material);
+        } else {
+            let label_entity = cmd
+                .commands()
+                .spawn(PbrBundle {
+                    mesh: shots_labels.mesh.clone(),
+                    material: label_material,
+                    transform: Transform::from_xyz(0.0, 1.2, 0.5),
+                    ..Default::default()
+                })
+                .id();
+            cmd.add_child(label_entity);
+            cmd.insert(ShotsLabel(label_entity));
+        }
+        if ctx.is_first_time() {
+            cmd.insert(VpeolWillContainClickableChildren);
+            cmd.insert(VisibilityBundle::default());
             cmd.insert(RigidBody::Fixed);
             cmd.insert(Collider::capsule_y(0.5, 0.5));
             cmd.insert(Sensor);
@@ -81,77 +262,361 @@ fn initiate_pickup(
     }
 }
 
+const HARVEST_RANGE: f32 = 1.5;
+
+#[allow(clippy::type_complexity)]
+fn initiate_harvest(
+    mut reader: EventReader<HarvestEvent>,
+    harvester_query: Query<&GlobalTransform, With<CanCarry>>,
+    harvestable_query: Query<(), (With<Planted>, Without<Growing>, Without<Withering>)>,
+    rapier_context: Res<RapierContext>,
+    mut writer: EventWriter<PickEvent>,
+) {
+    for event in reader.iter() {
+        let Ok(harvester_transform) = harvester_query.get(event.harvester_entity) else { continue };
+        let mut harvested = None;
+        rapier_context.intersections_with_shape(
+            harvester_transform.translation().truncate(),
+            0.0,
+            &Collider::ball(HARVEST_RANGE),
+            QueryFilter::new(),
+            |entity| {
+                if harvestable_query.contains(entity) {
+                    harvested = Some(entity);
+                    false
+                } else {
+                    true
+                }
+            },
+        );
+        if let Some(harvested) = harvested {
+            writer.send(PickEvent {
+                picker: event.harvester_entity,
+                pickable: harvested,
+            });
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct CarriedAmmunition {
-    remaining_shots: usize,
+    pub remaining_shots: usize,
+    /// Pickups waiting for this stack to be used up before they regrow.
+    respawn_sources: Vec<Entity>,
 }
 
 #[derive(Component, Default, Debug)]
 pub struct CanCarry {
-    pub carries: Option<Entity>,
+    /// The carried ammunition entities - one stack per plant type.
+    pub stacks: Vec<Entity>,
+    /// The index in `stacks` of the ammunition that gets shot.
+    pub selected: usize,
+}
+
+impl CanCarry {
+    pub fn carries(&self) -> Option<Entity> {
+        self.stacks.get(self.selected).copied()
+    }
+
+    fn remove_stack(&mut self, stack: Entity) {
+        self.stacks.retain(|entity| *entity != stack);
+        if self.stacks.len() <= self.selected {
+            self.selected = 0;
+        }
+    }
 }
 
 fn handle_carrying(
     mut reader: EventReader<PickEvent>,
     mut can_carry_query: Query<(&mut CanCarry, &ApplyRotationToChild)>,
-    plant_type_query: Query<&PlantType>,
+    pickable_query: Query<(&PlantType, Option<&AmmoShots>, Option<&AmmoRespawn>)>,
+    mut stacks_query: Query<(&PlantType, &mut CarriedAmmunition, &mut Transform)>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    plant_definitions: Res<PlantDefinitions>,
 ) {
     for event in reader.iter() {
         let Ok((mut can_carry, ApplyRotationToChild(model_entity))) = can_carry_query.get_mut(event.picker) else { continue };
-        if can_carry.carries.is_some() {
+        let Ok((plant_type, shots, respawn)) = pickable_query.get(event.pickable) else { continue };
+        let definition = plant_definitions.get(plant_type);
+        let shots = shots
+            .and_then(|AmmoShots(shots)| *shots)
+            .unwrap_or(definition.shots);
+        let mut respawn_source = None;
+        match respawn.unwrap_or(&AmmoRespawn::Never) {
+            AmmoRespawn::Never => {
+                commands.entity(event.pickable).despawn_recursive();
+            }
+            AmmoRespawn::AfterSeconds(seconds) => {
+                hide_pickable(
+                    &mut commands.entity(event.pickable),
+                    Some(Timer::from_seconds(*seconds, TimerMode::Once)),
+                );
+            }
+            AmmoRespawn::WhenUsedUp => {
+                hide_pickable(&mut commands.entity(event.pickable), None);
+                respawn_source = Some(event.pickable);
+            }
+        }
+
+        let mut stacks = stacks_query.iter_many_mut(&can_carry.stacks);
+        let mut merged = false;
+        while let Some((stack_plant_type, mut carried_ammunition, mut transform)) =
+            stacks.fetch_next()
+        {
+            if stack_plant_type == plant_type {
+                carried_ammunition.remaining_shots += shots;
+                carried_ammunition.respawn_sources.extend(respawn_source);
+                transform.scale =
+                    Vec3::ONE * carried_scale(definition, carried_ammunition.remaining_shots);
+                merged = true;
+                break;
+            }
+        }
+        if merged {
             continue;
         }
-        let Ok(plant_type) = plant_type_query.get(event.pickable) else { continue };
-        commands.entity(event.pickable).despawn_recursive();
+
+        // `show_selected_ammunition` only sees the new stack once it is spawned, so it must start
+        // out hidden if it is not the selected one.
+        let visibility = if can_carry.stacks.len() == can_carry.selected {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
         commands.entity(*model_entity).with_children(|commands| {
             let mut cmd = commands.spawn_empty();
-            cmd.insert(CarriedAmmunition { remaining_shots: 3 });
+            cmd.insert(CarriedAmmunition {
+                remaining_shots: shots,
+                respawn_sources: respawn_source.into_iter().collect(),
+            });
             cmd.insert(plant_type.clone());
             cmd.insert(SceneBundle {
-                scene: asset_server.load(plant_type.scene_name()),
-                transform: Transform::from_xyz(0.0, 1.0, 1.0).with_scale(Vec3::ONE * 0.5),
+                scene: asset_server.load(definition.scene.as_str()),
+                transform: Transform::from_xyz(0.0, 1.0, 1.0)
+                    .with_scale(Vec3::ONE * carried_scale(definition, shots)),
+                visibility,
                 ..Default::default()
             });
-            can_carry.carries = Some(cmd.id());
+            can_carry.stacks.push(cmd.id());
         });
     }
 }
 
+fn handle_cycling(
+    mut reader: EventReader<CycleAmmunitionEvent>,
+    mut can_carry_query: Query<&mut CanCarry>,
+) {
+    for event in reader.iter() {
+        let Ok(mut can_carry) = can_carry_query.get_mut(event.carrier_entity) else { continue };
+        if can_carry.stacks.len() < 2 {
+            continue;
+        }
+        can_carry.selected = (can_carry.selected + 1) % can_carry.stacks.len();
+    }
+}
+
+/// Only the selected ammunition is shown on the carrier.
+fn show_selected_ammunition(
+    can_carry_query: Query<&CanCarry, Changed<CanCarry>>,
+    mut visibility_query: Query<&mut Visibility, With<CarriedAmmunition>>,
+) {
+    for can_carry in can_carry_query.iter() {
+        for (index, stack) in can_carry.stacks.iter().enumerate() {
+            let Ok(mut visibility) = visibility_query.get_mut(*stack) else { continue };
+            *visibility = if index == can_carry.selected {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+/// The carried ammunition shrinks with each shot, down to 40% of its full size.
+fn carried_scale(definition: &PlantDefinition, remaining_shots: usize) -> f32 {
+    let remaining_fraction = (remaining_shots as f32 / definition.shots.max(1) as f32).min(1.0);
+    definition.carried_scale * (0.4 + 0.6 * remaining_fraction)
+}
+
 pub struct UseUpShotEvent {
     pub carrier_entity: Entity,
     pub carried_ammunition_entity: Entity,
     pub ejecet_direction: Vec3,
+    pub shots: usize,
 }
 
 fn handle_useup(
     mut reader: EventReader<UseUpShotEvent>,
-    mut query: Query<(&mut CarriedAmmunition, &mut Transform, &GlobalTransform)>,
+    mut query: Query<(
+        &mut CarriedAmmunition,
+        &PlantType,
+        &mut Transform,
+        &GlobalTransform,
+    )>,
     mut carrier_query: Query<&mut CanCarry>,
+    mut awaiting_respawn_query: Query<&mut AwaitingRespawn>,
     mut commands: Commands,
+    plant_definitions: Res<PlantDefinitions>,
 ) {
     for event in reader.iter() {
-        let Ok((mut carried_ammunition, mut transform, global_transform)) = query.get_mut(event.carried_ammunition_entity) else { continue };
-        carried_ammunition.remaining_shots -= 1;
-        transform.scale -= Vec3::ONE * 0.1;
+        let Ok((mut carried_ammunition, plant_type, mut transform, global_transform)) = query.get_mut(event.carried_ammunition_entity) else { continue };
+        let definition = plant_definitions.get(plant_type);
+        carried_ammunition.remaining_shots = carried_ammunition
+            .remaining_shots
+            .saturating_sub(event.shots);
+        transform.scale = Vec3::ONE * carried_scale(definition, carried_ammunition.remaining_shots);
 
         if carried_ammunition.remaining_shots == 0 {
+            release_respawn_sources(&mut carried_ammunition, &mut awaiting_respawn_query);
             if let Ok(mut can_carry) = carrier_query.get_mut(event.carrier_entity) {
-                can_carry.carries = None;
+                can_carry.remove_stack(event.carried_ammunition_entity);
             }
             *transform = global_transform.compute_transform();
-            let mut cmd = commands.entity(event.carried_ammunition_entity);
-            cmd.remove_parent();
-            cmd.insert(RigidBody::Dynamic);
-            cmd.insert(Collider::capsule_y(0.5, 0.5));
-            cmd.insert(Velocity {
-                linvel: 3.0 * event.ejecet_direction.truncate() + 20.0 * Vec2::Y,
-                angvel: -10.0 * event.ejecet_direction.x,
-            });
-            cmd.insert(ActiveEvents::COLLISION_EVENTS);
-            cmd.insert(FlyingSeed);
-            cmd.insert(YoleckBelongsToLevel);
+            let seed = &definition.seed;
+            eject_seed(
+                &mut commands.entity(event.carried_ammunition_entity),
+                seed,
+                Velocity {
+                    linvel: seed.eject_speed * event.ejecet_direction.truncate()
+                        + seed.eject_lift * Vec2::Y,
+                    angvel: -seed.spin * event.ejecet_direction.x,
+                },
+            );
+        }
+    }
+}
+
+const THROW_SPEED: f32 = 12.0;
+
+/// Drop or throw the selected stack as a seed. Its remaining shots are forfeited.
+fn handle_dropping(
+    mut reader: EventReader<DropAmmunitionEvent>,
+    mut carrier_query: Query<&mut CanCarry>,
+    mut query: Query<(
+        &mut CarriedAmmunition,
+        &PlantType,
+        &mut Transform,
+        &GlobalTransform,
+    )>,
+    mut awaiting_respawn_query: Query<&mut AwaitingRespawn>,
+    mut commands: Commands,
+    plant_definitions: Res<PlantDefinitions>,
+) {
+    for event in reader.iter() {
+        let Ok(mut can_carry) = carrier_query.get_mut(event.carrier_entity) else { continue };
+        let Some(stack_entity) = can_carry.carries() else { continue };
+        let Ok((mut carried_ammunition, plant_type, mut transform, global_transform)) = query.get_mut(stack_entity) else { continue };
+        can_carry.remove_stack(stack_entity);
+        release_respawn_sources(&mut carried_ammunition, &mut awaiting_respawn_query);
+        carried_ammunition.remaining_shots = 0;
+        *transform = global_transform.compute_transform();
+        let seed = &plant_definitions.get(plant_type).seed;
+        let linvel = if event.thrown {
+            THROW_SPEED * event.direction.truncate()
+        } else {
+            seed.eject_speed * event.direction.truncate()
+        };
+        eject_seed(
+            &mut commands.entity(stack_entity),
+            seed,
+            Velocity {
+                linvel,
+                angvel: -seed.spin * event.direction.x,
+            },
+        );
+    }
+}
+
+/// Let the pickups the stack was picked from regrow, now that the stack is gone.
+fn release_respawn_sources(
+    carried_ammunition: &mut CarriedAmmunition,
+    awaiting_respawn_query: &mut Query<&mut AwaitingRespawn>,
+) {
+    let respawn_sources = std::mem::take(&mut carried_ammunition.respawn_sources);
+    let mut awaiting = awaiting_respawn_query.iter_many_mut(&respawn_sources);
+    while let Some(mut awaiting_respawn) = awaiting.fetch_next() {
+        awaiting_respawn.0 = Some(Timer::default());
+    }
+}
+
+/// Turn a carried stack into a flying seed. Its transform must already be in world space.
+fn eject_seed(cmd: &mut EntityCommands, seed: &SeedDefinition, velocity: Velocity) {
+    cmd.remove_parent();
+    cmd.insert(RigidBody::Dynamic);
+    cmd.insert(Collider::capsule_y(
+        seed.collider_half_height,
+        seed.collider_radius,
+    ));
+    cmd.insert(velocity);
+    cmd.insert(Restitution::coefficient(seed.restitution));
+    cmd.insert(Friction::coefficient(seed.friction));
+    cmd.insert(ActiveEvents::COLLISION_EVENTS);
+    cmd.insert(FlyingSeed::new(seed));
+    cmd.insert(YoleckBelongsToLevel);
+}
+
+/// A picked pickup that is hidden until it regrows. Without a timer, it waits for the stack it was
+/// picked into to be used up.
+#[derive(Component)]
+struct AwaitingRespawn(Option<Timer>);
+
+/// A pickup that is growing back. It can only be picked once fully grown.
+#[derive(Component)]
+struct Regrowing(Timer);
+
+const REGROWTH_DURATION: f32 = 1.0;
+
+fn hide_pickable(cmd: &mut EntityCommands, timer: Option<Timer>) {
+    cmd.remove::<Pickable>();
+    cmd.insert(ColliderDisabled);
+    cmd.insert(Visibility::Hidden);
+    cmd.insert(AwaitingRespawn(timer));
+}
+
+fn start_pickable_regrowth(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut AwaitingRespawn, &mut Visibility, &Children)>,
+    mut model_query: Query<&mut Transform, With<PickableModel>>,
+    mut commands: Commands,
+) {
+    for (entity, mut awaiting_respawn, mut visibility, children) in query.iter_mut() {
+        let Some(timer) = awaiting_respawn.0.as_mut() else { continue };
+        if !timer.tick(time.delta()).finished() {
+            continue;
+        }
+        *visibility = Visibility::Inherited;
+        let mut models = model_query.iter_many_mut(children);
+        while let Some(mut transform) = models.fetch_next() {
+            transform.scale = Vec3::ZERO;
+        }
+        let mut cmd = commands.entity(entity);
+        cmd.remove::<AwaitingRespawn>();
+        cmd.insert(Regrowing(Timer::from_seconds(
+            REGROWTH_DURATION,
+            TimerMode::Once,
+        )));
+    }
+}
+
+fn apply_pickable_regrowth(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Regrowing, &Children)>,
+    mut model_query: Query<&mut Transform, With<PickableModel>>,
+    mut commands: Commands,
+) {
+    for (entity, mut regrowing, children) in query.iter_mut() {
+        let Regrowing(timer) = regrowing.as_mut();
+        timer.tick(time.delta());
+        let mut models = model_query.iter_many_mut(children);
+        while let Some(mut transform) = models.fetch_next() {
+            transform.scale = Vec3::ONE * PICKABLE_MODEL_SCALE * timer.percent();
+        }
+        if timer.finished() {
+            let mut cmd = commands.entity(entity);
+            cmd.remove::<Regrowing>();
+            cmd.remove::<ColliderDisabled>();
+            cmd.insert(Pickable);
         }
     }
 }
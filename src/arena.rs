@@ -5,7 +5,7 @@ use bevy_yoleck::vpeol::prelude::*;
 
 use crate::editing_helpers::{GridSize, SnapToGrid};
 use crate::killing::KillPlayerWhenBelow;
-use crate::planting::FertileGround;
+use crate::planting::Soil;
 use crate::shooting::DestroysBullets;
 
 pub struct ArenaPlugin;
@@ -16,12 +16,15 @@ impl Plugin for ArenaPlugin {
             YoleckEntityType::new("Block")
                 .with::<Vpeol3dPosition>()
                 .with::<GridSize>()
+                .with::<Soil>()
                 .insert_on_init_during_editor(|| SnapToGrid)
-                .insert_on_init_during_editor(PreviousSize::default)
+                .insert_on_init_during_editor(PreviouslyPopulated::default)
                 .insert_on_init(|| IsBlock)
         });
         app.yoleck_populate_schedule_mut()
             .add_system(populate_block);
+        app.init_resource::<SoilMaterials>();
+        app.add_system(apply_soil_materials);
     }
 }
 
@@ -29,19 +32,26 @@ impl Plugin for ArenaPlugin {
 struct IsBlock;
 
 #[derive(Component, Default)]
-struct PreviousSize(UVec2);
+struct PreviouslyPopulated {
+    size: UVec2,
+    soil: Soil,
+}
 
 fn populate_block(
-    mut populate: YoleckPopulate<(&GridSize, Option<&mut PreviousSize>), With<IsBlock>>,
+    mut populate: YoleckPopulate<
+        (&GridSize, &Soil, Option<&mut PreviouslyPopulated>),
+        With<IsBlock>,
+    >,
     asset_server: Res<AssetServer>,
     marking: YoleckMarking,
 ) {
-    populate.populate(|_ctx, mut cmd, (size, mut previous_size)| {
-        let should_populate = if let Some(previous_size) = previous_size.as_mut() {
-            if size.0 == previous_size.0 {
+    populate.populate(|_ctx, mut cmd, (size, soil, mut previously_populated)| {
+        let should_populate = if let Some(previously_populated) = previously_populated.as_mut() {
+            if size.0 == previously_populated.size && *soil == previously_populated.soil {
                 false
             } else {
-                previous_size.0 = size.0;
+                previously_populated.size = size.0;
+                previously_populated.soil = soil.clone();
                 true
             }
         } else {
@@ -75,8 +85,61 @@ fn populate_block(
             size.0.x as f32 * 0.5,
             size.0.y as f32 * 0.5,
         ));
+        if *soil == Soil::Barren {
+            cmd.insert(Restitution::coefficient(0.8));
+        } else {
+            cmd.insert(Restitution::default());
+        }
         cmd.insert(DestroysBullets);
-        cmd.insert(FertileGround);
         cmd.insert(KillPlayerWhenBelow);
     })
 }
+
+/// Materials for blocks that don't use the model's original (fertile) material.
+#[derive(Resource)]
+struct SoilMaterials {
+    barren: Handle<StandardMaterial>,
+    rich: Handle<StandardMaterial>,
+    poisoned: Handle<StandardMaterial>,
+}
+
+impl FromWorld for SoilMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut material_assets = world.resource_mut::<Assets<StandardMaterial>>();
+        let mut material = |color: Color| {
+            material_assets.add(StandardMaterial {
+                base_color: color,
+                perceptual_roughness: 0.5,
+                ..Default::default()
+            })
+        };
+        Self {
+            barren: material(Color::rgb(0.5, 0.5, 0.45)),
+            rich: material(Color::rgb(0.3, 0.17, 0.05)),
+            poisoned: material(Color::rgb(0.45, 0.15, 0.5)),
+        }
+    }
+}
+
+fn apply_soil_materials(
+    mut query: Query<(Entity, &mut Handle<StandardMaterial>), Added<Handle<StandardMaterial>>>,
+    parents_query: Query<&Parent>,
+    soil_query: Query<&Soil, With<IsBlock>>,
+    soil_materials: Res<SoilMaterials>,
+) {
+    for (entity, mut material) in query.iter_mut() {
+        let mut parent_entity = entity;
+        while let Ok(parent) = parents_query.get(parent_entity) {
+            parent_entity = **parent;
+            if let Ok(soil) = soil_query.get(parent_entity) {
+                match soil {
+                    Soil::Fertile => {}
+                    Soil::Barren => *material = soil_materials.barren.clone(),
+                    Soil::Rich => *material = soil_materials.rich.clone(),
+                    Soil::Poisoned => *material = soil_materials.poisoned.clone(),
+                }
+                break;
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 use bevy::render::camera::RenderTarget;
 use bevy::render::render_resource::{
@@ -73,83 +74,24 @@ fn populate_text(
     asset_server: Res<AssetServer>,
 ) {
     populate.populate(|ctx, mut cmd, (text_content, child_entities)| {
-        let text_entity;
-        if let Some(child_entities) = child_entities {
-            text_entity = child_entities.text_entity;
+        let text_entity = if let Some(child_entities) = child_entities {
+            child_entities.text_entity
         } else {
-            let mesh = mesh_assets.add(Mesh::from(shape::Quad {
-                size: Vec2::new(4.0, 1.0),
-                flip: false,
-            }));
-            let size = Extent3d {
-                width: 2048,
-                height: 512,
-                ..Default::default()
-            };
-            let mut texture = Image {
-                texture_descriptor: TextureDescriptor {
-                    label: None,
-                    size,
-                    mip_level_count: 1,
-                    sample_count: 1,
-                    dimension: TextureDimension::D2,
-                    format: TextureFormat::Bgra8UnormSrgb,
-                    usage: TextureUsages::TEXTURE_BINDING
-                        | TextureUsages::COPY_DST
-                        | TextureUsages::RENDER_ATTACHMENT,
-                    view_formats: &[],
+            setup_text_rendering(
+                &mut cmd,
+                Vec2::new(4.0, 1.0),
+                UVec2::new(2048, 512),
+                if ctx.is_in_editor() {
+                    ClearColorConfig::Custom(Color::GRAY)
+                } else {
+                    // ClearColorConfig::Custom(Color::WHITE.with_a(0.0))
+                    ClearColorConfig::None
                 },
-                ..Default::default()
-            };
-            texture.resize(size);
-            let texture = image_assets.add(texture);
-            let material = material_assets.add(StandardMaterial {
-                base_color_texture: Some(texture.clone()),
-                unlit: true,
-                // alpha_mode: AlphaMode::Blend,
-                alpha_mode: AlphaMode::Mask(0.5),
-                ..Default::default()
-            });
-            cmd.insert(PbrBundle {
-                mesh,
-                material,
-                ..Default::default()
-            });
-
-            text_entity = cmd
-                .commands()
-                .spawn(Text2dBundle {
-                    ..Default::default()
-                })
-                .insert(RenderLayers::layer(1))
-                .id();
-            cmd.add_child(text_entity);
-            let camera_entity = cmd
-                .commands()
-                .spawn(Camera2dBundle {
-                    camera: Camera {
-                        order: -1,
-                        target: RenderTarget::Image(texture),
-                        ..Default::default()
-                    },
-                    camera_2d: Camera2d {
-                        clear_color: if ctx.is_in_editor() {
-                            ClearColorConfig::Custom(Color::GRAY)
-                        } else {
-                            // ClearColorConfig::Custom(Color::WHITE.with_a(0.0))
-                            ClearColorConfig::None
-                        },
-                    },
-                    ..Default::default()
-                })
-                .insert(RenderLayers::layer(1))
-                .id();
-            cmd.add_child(camera_entity);
-            cmd.insert(FloatingTextChildren {
-                text_entity,
-                camera_entity,
-            });
-        }
+                &mut mesh_assets,
+                &mut image_assets,
+                &mut material_assets,
+            )
+        };
         cmd.commands()
             .entity(text_entity)
             .insert(Text::from_section(
@@ -163,6 +105,104 @@ fn populate_text(
     });
 }
 
+/// Make the entity a quad that shows text, rendered by its own camera to a texture. Returns the
+/// text entity, to put the [`Text`] on.
+pub fn setup_text_rendering(
+    cmd: &mut EntityCommands,
+    quad_size: Vec2,
+    texture_size: UVec2,
+    clear_color: ClearColorConfig,
+    mesh_assets: &mut Assets<Mesh>,
+    image_assets: &mut Assets<Image>,
+    material_assets: &mut Assets<StandardMaterial>,
+) -> Entity {
+    let mesh = mesh_assets.add(Mesh::from(shape::Quad {
+        size: quad_size,
+        flip: false,
+    }));
+    let (text_entity, material) = setup_text_texture(
+        cmd,
+        texture_size,
+        clear_color,
+        image_assets,
+        material_assets,
+    );
+    cmd.insert(PbrBundle {
+        mesh,
+        material,
+        ..Default::default()
+    });
+    text_entity
+}
+
+/// Give the entity children that render text to a texture. Returns the text entity, to put the
+/// [`Text`] on, and a material that shows the texture.
+pub fn setup_text_texture(
+    cmd: &mut EntityCommands,
+    texture_size: UVec2,
+    clear_color: ClearColorConfig,
+    image_assets: &mut Assets<Image>,
+    material_assets: &mut Assets<StandardMaterial>,
+) -> (Entity, Handle<StandardMaterial>) {
+    let size = Extent3d {
+        width: texture_size.x,
+        height: texture_size.y,
+        ..Default::default()
+    };
+    let mut texture = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..Default::default()
+    };
+    texture.resize(size);
+    let texture = image_assets.add(texture);
+    let material = material_assets.add(StandardMaterial {
+        base_color_texture: Some(texture.clone()),
+        unlit: true,
+        // alpha_mode: AlphaMode::Blend,
+        alpha_mode: AlphaMode::Mask(0.5),
+        ..Default::default()
+    });
+
+    let text_entity = cmd
+        .commands()
+        .spawn(Text2dBundle {
+            ..Default::default()
+        })
+        .insert(RenderLayers::layer(1))
+        .id();
+    cmd.add_child(text_entity);
+    let camera_entity = cmd
+        .commands()
+        .spawn(Camera2dBundle {
+            camera: Camera {
+                order: -1,
+                target: RenderTarget::Image(texture),
+                ..Default::default()
+            },
+            camera_2d: Camera2d { clear_color },
+            ..Default::default()
+        })
+        .insert(RenderLayers::layer(1))
+        .id();
+    cmd.add_child(camera_entity);
+    cmd.insert(FloatingTextChildren {
+        text_entity,
+        camera_entity,
+    });
+    (text_entity, material)
+}
+
 fn override_visible_entities(
     parents_query: Query<&FloatingTextChildren>,
     mut visible_entities_query: Query<&mut VisibleEntities>,
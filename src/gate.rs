@@ -5,7 +5,7 @@ use bevy_yoleck::vpeol::prelude::*;
 
 use crate::animating::{AnimationsOwner, GetClipsFrom};
 use crate::editing_helpers::SnapToGrid;
-use crate::killing::Killable;
+use crate::killing::Health;
 use crate::player::IsPlayer;
 use crate::utils::sensor_events_both_ways;
 use crate::AppState;
@@ -28,13 +28,17 @@ impl Plugin for GatePlugin {
 }
 
 #[derive(Component)]
-struct Gate {
-    is_open: bool,
+pub struct Gate {
+    pub is_open: bool,
 }
 
 #[derive(Component)]
 pub struct KeepGatesClosedWhenAlive;
 
+/// Keeps the gates closed for as long as the entity has it.
+#[derive(Component)]
+pub struct KeepGatesClosed;
+
 fn edit_gate_z_depth(mut edit: YoleckEdit<&mut Vpeol3dPosition, With<Gate>>) {
     let Ok(mut position) = edit.get_single_mut() else { return };
     position.0.z = -0.5;
@@ -58,12 +62,16 @@ fn populate_gate(mut populate: YoleckPopulate<(), With<Gate>>, asset_server: Res
 }
 
 fn initiate_gate_opening(
-    killables_query: Query<&Killable, With<KeepGatesClosedWhenAlive>>,
+    healths_query: Query<&Health, With<KeepGatesClosedWhenAlive>>,
+    keep_closed_query: Query<(), With<KeepGatesClosed>>,
     mut gates_query: Query<(&mut Gate, &AnimationsOwner)>,
     mut animation_players_query: Query<&mut AnimationPlayer>,
 ) {
-    for killable in killables_query.iter() {
-        if killable.still_alive {
+    if !keep_closed_query.is_empty() {
+        return;
+    }
+    for health in healths_query.iter() {
+        if health.is_alive() {
             return;
         }
     }
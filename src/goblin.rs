@@ -1,6 +1,8 @@
-use std::f32::consts::PI;
+use std::f32::consts::{FRAC_PI_2, PI};
 
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use bevy_egui::egui;
 use bevy_rapier2d::prelude::*;
 use bevy_tnua::{
     TnuaFreeFallBehavior, TnuaManualTurningOutput, TnuaPlatformerBundle, TnuaPlatformerConfig,
@@ -8,14 +10,18 @@ use bevy_tnua::{
 };
 use bevy_yoleck::prelude::*;
 use bevy_yoleck::vpeol::prelude::*;
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
 
 use crate::animating::{AnimationsOwner, ApplyRotationToChild, GetClipsFrom, InitialAnimation};
 use crate::editing_helpers::SnapToGrid;
 use crate::gate::KeepGatesClosedWhenAlive;
-use crate::killing::{KillEvent, Killable};
+use crate::killing::{DamageEvent, Health, MaxHealth};
+use crate::plant_definitions::BulletDefinition;
+use crate::planting::{Entanglable, Entangled, Growing, LiftedByGrowingPlants, Planted, Withering};
 use crate::player::IsPlayer;
-use crate::shooting::{Bullet, DestroysBullets};
-use crate::utils::events_both_ways;
+use crate::player_controls::ShootEvent;
+use crate::shooting::{CanShoot, DestroysBullets, Faction, InnateAmmunition, WeaponStats};
 use crate::AppState;
 
 pub struct GoblinPlugin;
@@ -25,120 +31,503 @@ impl Plugin for GoblinPlugin {
         app.add_yoleck_entity_type({
             YoleckEntityType::new("Goblin")
                 .with::<Vpeol3dPosition>()
+                .with::<GoblinBehavior>()
+                .with::<WeaponStats>()
+                .with::<MaxHealth>()
                 .insert_on_init(|| IsGoblin)
                 .insert_on_init(|| Vpeol3dRotatation(Quat::from_rotation_y(PI)))
                 .insert_on_init_during_editor(|| SnapToGrid)
         });
+        app.add_yoleck_edit_system(edit_goblin_behavior);
         app.yoleck_populate_schedule_mut()
             .add_system(populate_goblin);
         app.add_system(handle_goblin_hitting_stuff);
-        app.add_system(goblins_face_player.in_set(OnUpdate(AppState::Game)));
+        app.add_systems(
+            (
+                apply_goblin_behavior,
+                goblins_throw_at_player,
+                goblins_chop_plants.after(apply_goblin_behavior),
+            )
+                .in_set(OnUpdate(AppState::Game)),
+        );
     }
 }
 
 #[derive(Component)]
 pub struct IsGoblin;
 
+/// How a goblin moves. By default it stays in place and faces the player.
+#[derive(YoleckComponent, Default, Clone, PartialEq, Component, Serialize, Deserialize)]
+pub struct GoblinBehavior {
+    /// Horizontal offsets, from where the goblin was placed, of the two points it patrols
+    /// between.
+    pub patrol: Option<[f32; 2]>,
+    /// The goblin chases the player when they get closer than this.
+    pub chase_range: Option<f32>,
+    /// Whether the goblin jumps over ledges instead of stopping at them.
+    pub jump_at_ledges: bool,
+    /// The goblin throws projectiles at the player when they get closer than this, at the fire
+    /// rate of its [`WeaponStats`].
+    pub throw_range: Option<f32>,
+    /// The goblin goes after planted plants closer than this to chop them down.
+    pub chop_range: Option<f32>,
+}
+
+fn edit_goblin_behavior(
+    mut ui: ResMut<YoleckUi>,
+    mut edit: YoleckEdit<(&Vpeol3dPosition, &mut GoblinBehavior)>,
+    mut knobs: YoleckKnobs,
+    asset_server: Res<AssetServer>,
+) {
+    let Ok((position, mut behavior)) = edit.get_single_mut() else { return };
+
+    let mut patrols = behavior.patrol.is_some();
+    ui.checkbox(&mut patrols, "Patrol");
+    if patrols != behavior.patrol.is_some() {
+        behavior.patrol = patrols.then_some([-3.0, 3.0]);
+    }
+
+    ui.horizontal(|ui| {
+        let mut chases = behavior.chase_range.is_some();
+        ui.checkbox(&mut chases, "Chase");
+        if chases {
+            let range = behavior.chase_range.get_or_insert(8.0);
+            ui.add(egui::Slider::new(range, 1.0..=30.0).text("range"));
+        } else {
+            behavior.chase_range = None;
+        }
+    });
+
+    ui.checkbox(&mut behavior.jump_at_ledges, "Jump at Ledges");
+
+    ui.horizontal(|ui| {
+        let mut throws = behavior.throw_range.is_some();
+        ui.checkbox(&mut throws, "Throw");
+        if throws {
+            let range = behavior.throw_range.get_or_insert(12.0);
+            ui.add(egui::Slider::new(range, 1.0..=30.0).text("range"));
+        } else {
+            behavior.throw_range = None;
+        }
+    });
+
+    ui.horizontal(|ui| {
+        let mut chops = behavior.chop_range.is_some();
+        ui.checkbox(&mut chops, "Chop Plants");
+        if chops {
+            let range = behavior.chop_range.get_or_insert(8.0);
+            ui.add(egui::Slider::new(range, 1.0..=30.0).text("range"));
+        } else {
+            behavior.chop_range = None;
+        }
+    });
+
+    let Some(patrol) = behavior.patrol.as_mut() else { return };
+    for (i, offset) in patrol.iter_mut().enumerate() {
+        let mut knob = knobs.knob(("patrol-point", i));
+        if knob.is_new {
+            knob.cmd.insert(VpeolWillContainClickableChildren);
+            knob.cmd.insert(SceneBundle {
+                scene: asset_server.load("ResizeMarker.glb#Scene0"),
+                ..Default::default()
+            });
+        }
+        knob.cmd.insert({
+            Transform::from_translation(position.0 + Vec3::X * *offset)
+                .with_rotation(Quat::from_rotation_z(i as f32 * FRAC_PI_2))
+        });
+        if let Some(new_point) = knob.get_passed_data::<Vec3>() {
+            *offset = (new_point.x - position.0.x).round();
+        }
+    }
+}
+
 fn populate_goblin(
-    mut populate: YoleckPopulate<(), With<IsGoblin>>,
+    mut populate: YoleckPopulate<&MaxHealth, With<IsGoblin>>,
     asset_server: Res<AssetServer>,
 ) {
-    populate.populate(|ctx, mut cmd, ()| {
+    populate.populate(|ctx, mut cmd, max_health| {
         if ctx.is_first_time() {
             cmd.insert(VpeolWillContainClickableChildren);
-            let child = cmd
-                .commands()
-                .spawn(SceneBundle {
-                    scene: asset_server.load("Goblin.glb#Scene0"),
-                    ..Default::default()
-                })
-                .id();
-            cmd.add_child(child);
-            cmd.insert(ApplyRotationToChild(child));
-        }
-        cmd.insert(VisibilityBundle::default());
-        cmd.insert(RigidBody::Dynamic);
-        cmd.insert(Velocity::default());
-        cmd.insert(Collider::capsule_y(0.7, 0.5));
-
-        cmd.insert(TnuaPlatformerBundle::new_with_config(
-            TnuaPlatformerConfig {
-                full_speed: 12.0,
-                full_jump_height: 4.0,
-                up: Vec3::Y,
-                forward: Vec3::X,
-                float_height: 1.2,
-                cling_distance: 1.0,
-                spring_strengh: 400.0,
-                spring_dampening: 1.4,
-                acceleration: 40.0,
-                air_acceleration: 20.0,
-                coyote_time: 0.15,
-                jump_start_extra_gravity: 30.0,
-                jump_fall_extra_gravity: 20.0,
-                jump_shorten_extra_gravity: 40.0,
-                free_fall_behavior: TnuaFreeFallBehavior::LikeJumpShorten,
-                tilt_offset_angvel: 5.0,
-                tilt_offset_angacl: 500.0,
-                turning_angvel: 10.0,
-            },
-        ));
-        cmd.insert(LockedAxes::ROTATION_LOCKED);
-        cmd.insert(TnuaRapier2dSensorShape(Collider::cuboid(0.45, 0.0)));
-        cmd.insert(TnuaManualTurningOutput::default());
-        cmd.insert(ActiveEvents::COLLISION_EVENTS);
-        cmd.insert(AnimationsOwner::default());
-        cmd.insert(GetClipsFrom(asset_server.load("Goblin.glb")));
-        cmd.insert(DestroysBullets);
-        cmd.insert(Killable::default());
-        cmd.insert(SolverGroups {
-            memberships: crate::solver_groups::GOBLIN,
-            filters: Group::NONE,
-        });
-        cmd.insert(InitialAnimation::new("Armature", "Dance"));
+            spawn_goblin_model(&mut cmd, &asset_server);
+        }
+        setup_goblin(&mut cmd, &asset_server, max_health);
         cmd.insert(KeepGatesClosedWhenAlive);
     });
 }
 
+/// Add the model and the AI state of a new goblin.
+pub fn spawn_goblin_model(cmd: &mut EntityCommands, asset_server: &AssetServer) {
+    let child = cmd
+        .commands()
+        .spawn(SceneBundle {
+            scene: asset_server.load("Goblin.glb#Scene0"),
+            ..Default::default()
+        })
+        .id();
+    cmd.add_child(child);
+    cmd.insert(ApplyRotationToChild(child));
+    cmd.insert(PatrolTarget(0));
+    cmd.insert(Chopper {
+        target: None,
+        timer: Timer::from_seconds(CHOP_INTERVAL, TimerMode::Repeating),
+    });
+}
+
+/// Add the physics, controls and combat components of a goblin.
+pub fn setup_goblin(cmd: &mut EntityCommands, asset_server: &AssetServer, max_health: &MaxHealth) {
+    cmd.insert(VisibilityBundle::default());
+    cmd.insert(RigidBody::Dynamic);
+    cmd.insert(Velocity::default());
+    cmd.insert(Collider::capsule_y(0.7, 0.5));
+
+    cmd.insert(TnuaPlatformerBundle::new_with_config(
+        TnuaPlatformerConfig {
+            full_speed: 12.0,
+            full_jump_height: 4.0,
+            up: Vec3::Y,
+            forward: Vec3::X,
+            float_height: 1.2,
+            cling_distance: 1.0,
+            spring_strengh: 400.0,
+            spring_dampening: 1.4,
+            acceleration: 40.0,
+            air_acceleration: 20.0,
+            coyote_time: 0.15,
+            jump_start_extra_gravity: 30.0,
+            jump_fall_extra_gravity: 20.0,
+            jump_shorten_extra_gravity: 40.0,
+            free_fall_behavior: TnuaFreeFallBehavior::LikeJumpShorten,
+            tilt_offset_angvel: 5.0,
+            tilt_offset_angacl: 500.0,
+            turning_angvel: 10.0,
+        },
+    ));
+    cmd.insert(LockedAxes::ROTATION_LOCKED);
+    cmd.insert(TnuaRapier2dSensorShape(Collider::cuboid(0.45, 0.0)));
+    cmd.insert(TnuaManualTurningOutput::default());
+    cmd.insert(ActiveEvents::COLLISION_EVENTS);
+    cmd.insert(AnimationsOwner::default());
+    cmd.insert(GetClipsFrom(asset_server.load("Goblin.glb")));
+    cmd.insert(DestroysBullets);
+    cmd.insert(Faction::Goblins);
+    cmd.insert(CanShoot::default());
+    cmd.insert(InnateAmmunition(BulletDefinition {
+        speed: 10.0,
+        gravity: 0.0,
+        pierces: false,
+        count: 1,
+        spread_degrees: 0.0,
+        ricochets: 0,
+    }));
+    cmd.insert(Health::new(max_health.0));
+    cmd.insert(LiftedByGrowingPlants);
+    cmd.insert(Entanglable);
+    cmd.insert(SolverGroups {
+        memberships: crate::solver_groups::GOBLIN,
+        filters: crate::solver_groups::PLANTED,
+    });
+    cmd.insert(InitialAnimation::new("Armature", "Dance"));
+}
+
+/// Goblins keep hurting the player for as long as they touch, so that a player who stays inside a
+/// goblin gets hit again once their invulnerability wears off.
+#[allow(clippy::type_complexity)]
 fn handle_goblin_hitting_stuff(
-    mut reader: EventReader<CollisionEvent>,
-    goblin_query: Query<&Killable, With<IsGoblin>>,
-    bullet_query: Query<(), With<Bullet>>,
-    player_query: Query<(), With<IsPlayer>>,
-    mut kill_events_writer: EventWriter<KillEvent>,
+    rapier_context: Res<RapierContext>,
+    // Entangled goblins can't hurt anyone.
+    goblin_query: Query<(Entity, &Health), (With<IsGoblin>, Without<Entangled>)>,
+    player_query: Query<&Health, With<IsPlayer>>,
+    mut damage_events_writer: EventWriter<DamageEvent>,
 ) {
-    for (e1, e2) in events_both_ways(&mut reader) {
-        if let Ok(goblin_killable) = goblin_query.get(e1) {
-            if !goblin_killable.still_alive {
+    for (goblin_entity, goblin_health) in goblin_query.iter() {
+        if !goblin_health.is_alive() {
+            continue;
+        }
+        for contact_pair in rapier_context.contacts_with(goblin_entity) {
+            if !contact_pair.has_any_active_contacts() {
                 continue;
             }
-        } else {
-            continue; // not a goblin
-        }
-        if bullet_query.contains(e2) {
-            kill_events_writer.send(KillEvent { entity_to_kill: e1 })
-        } else if player_query.contains(e2) {
-            kill_events_writer.send(KillEvent { entity_to_kill: e2 })
+            let other_entity = if contact_pair.collider1() == goblin_entity {
+                contact_pair.collider2()
+            } else {
+                contact_pair.collider1()
+            };
+            let Ok(player_health) = player_query.get(other_entity) else { continue };
+            if player_health.is_invulnerable() {
+                continue;
+            }
+            damage_events_writer.send(DamageEvent {
+                entity_to_damage: other_entity,
+                amount: 1,
+                source: goblin_entity,
+            });
         }
     }
 }
 
-fn goblins_face_player(
+/// The index of the patrol point the goblin is walking toward.
+#[derive(Component)]
+struct PatrolTarget(usize);
+
+/// The plant the goblin is currently chopping.
+#[derive(Component)]
+struct Chopper {
+    target: Option<Entity>,
+    timer: Timer,
+}
+
+const PATROL_SPEED: f32 = 0.3;
+const CHASE_SPEED: f32 = 0.6;
+/// How close a patrolling goblin needs to get to a patrol point before turning back.
+const PATROL_POINT_REACHED: f32 = 0.2;
+/// How far ahead of the goblin's center to look for ground.
+const LEDGE_PROBE_AHEAD: f32 = 0.8;
+/// How far down to look for ground. A bit more than the goblin's float height.
+const LEDGE_PROBE_DEPTH: f32 = 1.8;
+/// How close a plant needs to be for the goblin to chop it.
+const CHOP_REACH: f32 = 1.2;
+/// Seconds between chops.
+const CHOP_INTERVAL: f32 = 0.8;
+/// Each chop shrinks the plant by this factor.
+const CHOP_SHRINK: f32 = 0.75;
+/// Plants chopped below this scale wither away.
+const CHOP_FELL_SCALE: f32 = 0.4;
+
+#[allow(clippy::type_complexity)]
+fn apply_goblin_behavior(
     player_query: Query<&GlobalTransform, With<IsPlayer>>,
     mut goblins_query: Query<
-        (&Killable, &GlobalTransform, &mut TnuaPlatformerControls),
-        With<IsGoblin>,
+        (
+            Entity,
+            &Health,
+            &GoblinBehavior,
+            &Vpeol3dPosition,
+            &mut PatrolTarget,
+            &mut Chopper,
+            &GlobalTransform,
+            &mut TnuaPlatformerControls,
+        ),
+        (With<IsGoblin>, Without<Entangled>),
     >,
+    plants_query: Query<(Entity, &GlobalTransform), (With<Planted>, Without<Withering>)>,
+    rapier_context: Res<RapierContext>,
 ) {
-    let Ok(player_transform) = player_query.get_single() else { return };
-    let player_position = player_transform.translation();
+    let player_position = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation());
 
-    for (killable, goblin_transform, mut goblin_controls) in goblins_query.iter_mut() {
-        if !killable.still_alive {
+    for (
+        entity,
+        health,
+        behavior,
+        placement,
+        mut patrol_target,
+        mut chopper,
+        goblin_transform,
+        mut controls,
+    ) in goblins_query.iter_mut()
+    {
+        chopper.target = None;
+        if !health.is_alive() {
+            controls.desired_velocity = Vec3::ZERO;
+            controls.jump = None;
             continue;
         }
         let goblin_position = goblin_transform.translation();
-        let vector_to_player = player_position - goblin_position;
-        goblin_controls.desired_forward = Vec3::X * vector_to_player.x.signum();
+        let vector_to_player = player_position.map(|position| position - goblin_position);
+
+        let chasing = vector_to_player.filter(|vector| {
+            behavior
+                .chase_range
+                .is_some_and(|range| vector.length() < range)
+        });
+        let ledge_ahead = |direction: f32| {
+            rapier_context
+                .cast_ray(
+                    goblin_position.truncate() + Vec2::X * direction * LEDGE_PROBE_AHEAD,
+                    -Vec2::Y,
+                    LEDGE_PROBE_DEPTH,
+                    true,
+                    QueryFilter::new()
+                        .exclude_rigid_body(entity)
+                        .exclude_sensors(),
+                )
+                .is_none()
+        };
+        // Whether the plant is at the goblin's height, within `horizontal_offset` plus `reach`.
+        // Plants on other platforms or on the ceiling are not.
+        let plant_at_goblin_level = |plant_entity: Entity, horizontal_offset: f32, reach: f32| {
+            let is_plant = |other: Entity| other == plant_entity;
+            rapier_context
+                .intersection_with_shape(
+                    goblin_position.truncate() + Vec2::X * 0.5 * horizontal_offset,
+                    0.0,
+                    &Collider::cuboid(0.5 * horizontal_offset.abs() + reach, CHOP_REACH),
+                    QueryFilter::new().predicate(&is_plant),
+                )
+                .is_some()
+        };
+        let plant_to_chop = behavior.chop_range.and_then(|range| {
+            let mut plants_in_range = plants_query
+                .iter()
+                .map(|(plant_entity, plant_transform)| {
+                    let vector_to_plant = plant_transform.translation() - goblin_position;
+                    (plant_entity, vector_to_plant)
+                })
+                .filter(|(_, vector_to_plant)| vector_to_plant.length() < range)
+                .collect::<Vec<_>>();
+            plants_in_range
+                .sort_by_key(|(_, vector_to_plant)| OrderedFloat(vector_to_plant.length()));
+            plants_in_range
+                .into_iter()
+                .find(|(plant_entity, vector_to_plant)| {
+                    plant_at_goblin_level(*plant_entity, vector_to_plant.x, 0.0)
+                })
+                .filter(|(plant_entity, vector_to_plant)| {
+                    // A goblin that can't jump can't get to plants past a ledge.
+                    behavior.jump_at_ledges
+                        || plant_at_goblin_level(*plant_entity, 0.0, CHOP_REACH)
+                        || !ledge_ahead(vector_to_plant.x.signum())
+                })
+        });
+        let mut face_x = vector_to_player.map(|vector| vector.x);
+
+        let mut patrolling = false;
+        let (direction, speed) = if let Some(vector_to_player) = chasing {
+            let direction = if PATROL_POINT_REACHED < vector_to_player.x.abs() {
+                vector_to_player.x.signum()
+            } else {
+                0.0
+            };
+            (direction, CHASE_SPEED)
+        } else if let Some((plant_entity, vector_to_plant)) = plant_to_chop {
+            face_x = Some(vector_to_plant.x);
+            if plant_at_goblin_level(plant_entity, 0.0, CHOP_REACH) {
+                chopper.target = Some(plant_entity);
+                (0.0, 0.0)
+            } else {
+                (vector_to_plant.x.signum(), PATROL_SPEED)
+            }
+        } else if let Some(patrol) = behavior.patrol {
+            patrolling = true;
+            let mut offset_to_target = placement.0.x + patrol[patrol_target.0] - goblin_position.x;
+            if offset_to_target.abs() < PATROL_POINT_REACHED {
+                patrol_target.0 = 1 - patrol_target.0;
+                offset_to_target = placement.0.x + patrol[patrol_target.0] - goblin_position.x;
+            }
+            (offset_to_target.signum(), PATROL_SPEED)
+        } else {
+            (0.0, 0.0)
+        };
+
+        let mut jump = None;
+        let mut stop = false;
+        if direction != 0.0 && ledge_ahead(direction) {
+            if behavior.jump_at_ledges {
+                jump = Some(1.0);
+            } else {
+                stop = true;
+                if patrolling {
+                    // Turn back at the ledge, so that the goblin does not get stuck there.
+                    patrol_target.0 = 1 - patrol_target.0;
+                }
+            }
+        }
+
+        controls.desired_velocity = if stop {
+            Vec3::ZERO
+        } else {
+            Vec3::X * direction * speed
+        };
+        controls.jump = jump;
+        if direction != 0.0 {
+            controls.desired_forward = Vec3::X * direction;
+        } else if let Some(face_x) = face_x {
+            controls.desired_forward = Vec3::X * face_x.signum();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn goblins_throw_at_player(
+    player_query: Query<(&Health, &GlobalTransform), With<IsPlayer>>,
+    goblins_query: Query<
+        (
+            Entity,
+            &Health,
+            &GoblinBehavior,
+            &CanShoot,
+            &WeaponStats,
+            &GlobalTransform,
+        ),
+        (With<IsGoblin>, Without<Entangled>),
+    >,
+    mut shoot_events_writer: EventWriter<ShootEvent>,
+) {
+    let Ok((player_health, player_transform)) = player_query.get_single() else { return };
+    if !player_health.is_alive() {
+        return;
+    }
+    let player_position = player_transform.translation();
+
+    for (goblin_entity, health, behavior, can_shoot, stats, goblin_transform) in
+        goblins_query.iter()
+    {
+        if !health.is_alive() || can_shoot.cooldown_progress() < 1.0 {
+            continue;
+        }
+        let Some(throw_range) = behavior.throw_range else { continue };
+        let throw_from = goblin_transform.translation()
+            + can_shoot.current_stats(stats).spawn_offset.y * Vec3::Y;
+        let vector_to_player = player_position - throw_from;
+        if throw_range < vector_to_player.length() {
+            continue;
+        }
+        let Some(direction) = vector_to_player.try_normalize() else { continue };
+        shoot_events_writer.send(ShootEvent {
+            shooter_entity: goblin_entity,
+            direction,
+            charged: false,
+        });
+    }
+}
+
+/// Chop the plant in reach, with the goblin's model squashing on each chop.
+fn goblins_chop_plants(
+    time: Res<Time>,
+    mut goblins_query: Query<(&mut Chopper, &ApplyRotationToChild, Option<&Entangled>)>,
+    mut transforms_query: Query<&mut Transform>,
+    mut commands: Commands,
+) {
+    for (mut chopper, ApplyRotationToChild(model_entity), entangled) in goblins_query.iter_mut() {
+        if entangled.is_some() {
+            // `apply_goblin_behavior` skips entangled goblins, so it won't clear the target.
+            chopper.target = None;
+        }
+        let Some(target) = chopper.target else {
+            chopper.timer.reset();
+            if let Ok(mut model_transform) = transforms_query.get_mut(*model_entity) {
+                model_transform.scale = Vec3::ONE;
+            }
+            continue;
+        };
+        let chopped = chopper.timer.tick(time.delta()).just_finished();
+        if let Ok(mut model_transform) = transforms_query.get_mut(*model_entity) {
+            let squash = 0.2 * (chopper.timer.percent() * PI).sin();
+            model_transform.scale = Vec3::new(1.0 + squash, 1.0 - squash, 1.0);
+        }
+        if !chopped {
+            continue;
+        }
+        let Ok(mut plant_transform) = transforms_query.get_mut(target) else { continue };
+        plant_transform.scale *= CHOP_SHRINK;
+        let mut cmd = commands.entity(target);
+        // Chopped plants stop growing.
+        cmd.remove::<Growing>();
+        if plant_transform.scale.min_element() < CHOP_FELL_SCALE {
+            cmd.insert(Withering { pace: 1.0 });
+        }
     }
 }
@@ -0,0 +1,186 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_yoleck::prelude::*;
+use bevy_yoleck::vpeol::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::editing_helpers::SnapToGrid;
+use crate::gate::{KeepGatesClosed, KeepGatesClosedWhenAlive};
+use crate::goblin::{setup_goblin, spawn_goblin_model, GoblinBehavior, IsGoblin};
+use crate::killing::{Health, MaxHealth};
+use crate::planting::Entangled;
+use crate::shooting::WeaponStats;
+use crate::AppState;
+
+pub struct GoblinSpawnerPlugin;
+
+impl Plugin for GoblinSpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        // The goblin components on the spawner are the template for the goblins it spawns.
+        app.add_yoleck_entity_type({
+            YoleckEntityType::new("GoblinSpawner")
+                .with::<Vpeol3dPosition>()
+                .with::<GoblinSpawner>()
+                .with::<GoblinBehavior>()
+                .with::<WeaponStats>()
+                .with::<MaxHealth>()
+                .insert_on_init_during_editor(|| SnapToGrid)
+        });
+        app.add_yoleck_edit_system(edit_goblin_spawner);
+        app.yoleck_populate_schedule_mut()
+            .add_system(populate_goblin_spawner);
+        app.add_system(spawn_goblins.in_set(OnUpdate(AppState::Game)));
+    }
+}
+
+#[derive(YoleckComponent, Clone, PartialEq, Component, Serialize, Deserialize)]
+pub struct GoblinSpawner {
+    /// Seconds between spawns.
+    pub interval: f32,
+    /// The spawner waits while this many of its goblins are alive.
+    pub max_alive: usize,
+    /// How many goblins the spawner spawns in total. `None` means it never stops, and its goblins
+    /// do not keep the gates closed.
+    pub budget: Option<usize>,
+}
+
+impl Default for GoblinSpawner {
+    fn default() -> Self {
+        Self {
+            interval: 5.0,
+            max_alive: 3,
+            budget: Some(10),
+        }
+    }
+}
+
+fn edit_goblin_spawner(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut GoblinSpawner>) {
+    let Ok(mut spawner) = edit.get_single_mut() else { return };
+    ui.add(
+        egui::Slider::new(&mut spawner.interval, 0.5..=60.0)
+            .prefix("Spawn Interval: ")
+            .suffix(" seconds"),
+    );
+    ui.add(egui::Slider::new(&mut spawner.max_alive, 1..=20).prefix("Max Alive: "));
+    ui.horizontal(|ui| {
+        let mut limited = spawner.budget.is_some();
+        ui.checkbox(&mut limited, "Budget");
+        if limited {
+            let budget = spawner.budget.get_or_insert(10);
+            ui.add(egui::DragValue::new(budget).clamp_range(1..=999));
+        } else {
+            spawner.budget = None;
+        }
+    });
+}
+
+#[derive(Component)]
+pub struct GoblinSpawnerState {
+    timer: Timer,
+    spawned: Vec<Entity>,
+    remaining_budget: Option<usize>,
+}
+
+impl GoblinSpawnerState {
+    pub fn remaining_budget(&self) -> Option<usize> {
+        self.remaining_budget
+    }
+}
+
+fn populate_goblin_spawner(
+    mut populate: YoleckPopulate<&GoblinSpawner>,
+    asset_server: Res<AssetServer>,
+) {
+    populate.populate(|ctx, mut cmd, spawner| {
+        if ctx.is_in_editor() {
+            if ctx.is_first_time() {
+                cmd.insert(VpeolWillContainClickableChildren);
+                cmd.insert(VisibilityBundle::default());
+                cmd.with_children(|commands| {
+                    commands.spawn(SceneBundle {
+                        scene: asset_server.load("Goblin.glb#Scene0"),
+                        transform: Transform::from_scale(Vec3::ONE * 0.5),
+                        ..Default::default()
+                    });
+                });
+            }
+            return;
+        }
+        cmd.insert(GoblinSpawnerState {
+            timer: {
+                let mut timer = Timer::from_seconds(spawner.interval, TimerMode::Once);
+                // trick it to start as finished, so that the first goblin spawns right away
+                timer.tick(timer.duration());
+                timer
+            },
+            spawned: Vec::new(),
+            remaining_budget: spawner.budget,
+        });
+        if spawner.budget.is_some() {
+            cmd.insert(KeepGatesClosed);
+        }
+    });
+}
+
+#[allow(clippy::type_complexity)]
+fn spawn_goblins(
+    time: Res<Time>,
+    mut spawners_query: Query<(
+        Entity,
+        &GoblinSpawner,
+        &mut GoblinSpawnerState,
+        &Vpeol3dPosition,
+        &GoblinBehavior,
+        &WeaponStats,
+        &MaxHealth,
+    )>,
+    goblins_query: Query<(&Health, Option<&Entangled>)>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    for (spawner_entity, spawner, mut state, position, behavior, stats, max_health) in
+        spawners_query.iter_mut()
+    {
+        state.timer.tick(time.delta());
+        // Goblins turned into platforms are out of the fight, and make room for new ones.
+        state.spawned.retain(|goblin| {
+            goblins_query.get(*goblin).is_ok_and(|(health, entangled)| {
+                health.is_alive() && !entangled.is_some_and(Entangled::is_permanent)
+            })
+        });
+        if state.remaining_budget == Some(0)
+            || !state.timer.finished()
+            || spawner.max_alive <= state.spawned.len()
+        {
+            continue;
+        }
+
+        let mut cmd = commands.spawn((
+            IsGoblin,
+            position.clone(),
+            behavior.clone(),
+            stats.clone(),
+            max_health.clone(),
+            TransformBundle::from_transform(
+                Transform::from_translation(position.0).with_rotation(Quat::from_rotation_y(PI)),
+            ),
+            YoleckBelongsToLevel,
+        ));
+        spawn_goblin_model(&mut cmd, &asset_server);
+        setup_goblin(&mut cmd, &asset_server, max_health);
+        if spawner.budget.is_some() {
+            cmd.insert(KeepGatesClosedWhenAlive);
+        }
+        state.spawned.push(cmd.id());
+
+        state.timer.reset();
+        if let Some(remaining_budget) = state.remaining_budget.as_mut() {
+            *remaining_budget -= 1;
+            if *remaining_budget == 0 {
+                commands.entity(spawner_entity).remove::<KeepGatesClosed>();
+            }
+        }
+    }
+}
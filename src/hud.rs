@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::ammunition::{CanCarry, CarriedAmmunition};
+use crate::gate::{Gate, KeepGatesClosedWhenAlive};
+use crate::goblin_spawner::GoblinSpawnerState;
+use crate::killing::Health;
+use crate::planting::PlantType;
+use crate::player::IsPlayer;
+use crate::shooting::CanShoot;
+use crate::AppState;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(draw_hud.in_set(OnUpdate(AppState::Game)));
+    }
+}
+
+fn draw_hud(
+    mut egui_contexts: EguiContexts,
+    players_query: Query<(&Health, &CanCarry, &CanShoot), With<IsPlayer>>,
+    stacks_query: Query<(&PlantType, &CarriedAmmunition)>,
+    gate_keepers_query: Query<&Health, With<KeepGatesClosedWhenAlive>>,
+    gates_query: Query<&Gate>,
+    spawners_query: Query<&GoblinSpawnerState>,
+) {
+    egui::Area::new("hud")
+        .anchor(egui::Align2::LEFT_TOP, egui::Vec2::new(8.0, 8.0))
+        .interactable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            for (health, can_carry, can_shoot) in players_query.iter() {
+                ui.label(format!("Health: {}/{}", health.current, health.max));
+                if can_carry.stacks.is_empty() {
+                    ui.label("No ammunition");
+                }
+                for (index, stack) in can_carry.stacks.iter().enumerate() {
+                    let Ok((plant_type, carried_ammunition)) = stacks_query.get(*stack) else { continue };
+                    let text = format!(
+                        "{} x{}",
+                        plant_type.name(),
+                        carried_ammunition.remaining_shots
+                    );
+                    if index == can_carry.selected {
+                        ui.label(egui::RichText::new(text).strong());
+                    } else {
+                        ui.label(egui::RichText::new(text).weak());
+                    }
+                }
+                ui.add(egui::ProgressBar::new(can_shoot.cooldown_progress()).desired_width(100.0));
+            }
+
+            let gate_keepers_alive = gate_keepers_query
+                .iter()
+                .filter(|health| health.is_alive())
+                .count();
+            // Goblins that spawners are yet to spawn count too.
+            let gate_keepers_to_spawn: usize = spawners_query
+                .iter()
+                .filter_map(|spawner| spawner.remaining_budget())
+                .sum();
+            let goblins_left = gate_keepers_alive + gate_keepers_to_spawn;
+            if 0 < goblins_left {
+                ui.label(format!("Goblins left: {}", goblins_left));
+            }
+            if gates_query.iter().any(|gate| gate.is_open) {
+                ui.label("The gate is open");
+            } else if !gates_query.is_empty() {
+                ui.label("The gate is closed");
+            }
+        });
+}
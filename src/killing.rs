@@ -1,7 +1,11 @@
 use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_rapier2d::prelude::*;
+use bevy_yoleck::prelude::*;
 use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
 
-use crate::animating::AnimationsOwner;
+use crate::animating::{AnimationsOwner, ApplyRotationToChild};
 use crate::editing_helpers::GridSize;
 use crate::player::IsPlayer;
 use crate::shooting::DestroysBullets;
@@ -11,45 +15,168 @@ pub struct KillingPlugin;
 
 impl Plugin for KillingPlugin {
     fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>();
         app.add_event::<KillEvent>();
-        app.add_systems((handle_killing, game_over_when_killing_player));
-        app.add_system(kill_player_when_they_fall.in_set(OnUpdate(AppState::Game)));
+        app.add_yoleck_edit_system(edit_max_health);
+        app.add_systems((handle_damage, handle_killing, game_over_when_killing_player).chain());
+        app.add_systems(
+            (
+                kill_player_when_they_fall,
+                update_invulnerability,
+                blink_while_invulnerable,
+            )
+                .in_set(OnUpdate(AppState::Game)),
+        );
     }
 }
 
+pub struct DamageEvent {
+    pub entity_to_damage: Entity,
+    pub amount: u32,
+    /// The entity that dealt the damage. The damaged entity is knocked away from it.
+    pub source: Entity,
+}
+
+/// Instant death, regardless of the remaining health.
 pub struct KillEvent {
     pub entity_to_kill: Entity,
 }
 
+#[derive(YoleckComponent, Clone, PartialEq, Component, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MaxHealth(pub u32);
+
+impl Default for MaxHealth {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+fn edit_max_health(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut MaxHealth>) {
+    let Ok(mut max_health) = edit.get_single_mut() else { return };
+    ui.add(
+        egui::Slider::new(&mut max_health.0, 1..=20)
+            .prefix("Health: ")
+            .suffix(" hits"),
+    );
+}
+
+const INVULNERABILITY_DURATION: f32 = 1.0;
+
+const KNOCKBACK_IMPULSE: f32 = 8.0;
+
 #[derive(Component)]
-pub struct Killable {
-    pub still_alive: bool,
+pub struct Health {
+    pub max: u32,
+    pub current: u32,
+    invulnerability: Timer,
 }
 
-impl Default for Killable {
-    fn default() -> Self {
-        Self { still_alive: true }
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Self {
+            max,
+            current: max,
+            invulnerability: {
+                let mut timer = Timer::from_seconds(INVULNERABILITY_DURATION, TimerMode::Once);
+                // trick it to start as finished
+                timer.tick(timer.duration());
+                timer
+            },
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        0 < self.current
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        !self.invulnerability.finished()
+    }
+}
+
+fn handle_damage(
+    mut reader: EventReader<DamageEvent>,
+    mut query: Query<(&mut Health, &GlobalTransform)>,
+    sources_query: Query<&GlobalTransform>,
+    mut commands: Commands,
+    mut kill_events_writer: EventWriter<KillEvent>,
+) {
+    for event in reader.iter() {
+        let Ok((mut health, transform)) = query.get_mut(event.entity_to_damage) else { continue };
+        if !health.is_alive() || health.is_invulnerable() {
+            continue;
+        }
+        if health.current <= event.amount {
+            // Dropping the health to zero is left to the kill handling.
+            kill_events_writer.send(KillEvent {
+                entity_to_kill: event.entity_to_damage,
+            });
+            continue;
+        }
+        health.current -= event.amount;
+        health.invulnerability.reset();
+        let Ok(source_transform) = sources_query.get(event.source) else { continue };
+        let away_from_source = (transform.translation() - source_transform.translation())
+            .truncate()
+            .try_normalize()
+            .unwrap_or(Vec2::Y);
+        // Knock upward a bit too, so that the knockback is not eaten by ground friction.
+        let impulse = KNOCKBACK_IMPULSE * (away_from_source + 0.5 * Vec2::Y);
+        commands
+            .entity(event.entity_to_damage)
+            .insert(ExternalImpulse {
+                impulse,
+                torque_impulse: 0.0,
+            });
+    }
+}
+
+fn update_invulnerability(time: Res<Time>, mut query: Query<&mut Health>) {
+    for mut health in query.iter_mut() {
+        health.invulnerability.tick(time.delta());
+    }
+}
+
+#[allow(clippy::manual_is_multiple_of)]
+fn blink_while_invulnerable(
+    query: Query<(&Health, &ApplyRotationToChild)>,
+    mut visibility_query: Query<&mut Visibility>,
+) {
+    for (health, ApplyRotationToChild(model_entity)) in query.iter() {
+        let Ok(mut visibility) = visibility_query.get_mut(*model_entity) else { continue };
+        let blinked_out = health.is_alive()
+            && health.is_invulnerable()
+            && (health.invulnerability.elapsed_secs() * 10.0) as u32 % 2 == 0;
+        let new_visibility = if blinked_out {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        if *visibility != new_visibility {
+            *visibility = new_visibility;
+        }
     }
 }
 
 fn handle_killing(
     mut reader: EventReader<KillEvent>,
-    mut query: Query<(&mut Killable, &AnimationsOwner)>,
+    mut query: Query<(&mut Health, &AnimationsOwner)>,
     mut commands: Commands,
     mut animation_players_query: Query<&mut AnimationPlayer>,
 ) {
     for event in reader.iter() {
-        let Ok((mut killable, animations_owner)) = query.get_mut(event.entity_to_kill) else {
-            error!("Entity {:?} is not killable", event.entity_to_kill);
+        let Ok((mut health, animations_owner)) = query.get_mut(event.entity_to_kill) else {
+            error!("Entity {:?} has no health", event.entity_to_kill);
             continue;
         };
         commands
             .entity(event.entity_to_kill)
             .remove::<DestroysBullets>();
-        if !killable.still_alive {
+        if !health.is_alive() {
             continue;
         }
-        killable.still_alive = false;
+        health.current = 0;
         let clip = animations_owner
             .clips
             .get("Death")
@@ -6,9 +6,12 @@ mod editing_helpers;
 mod floating_text;
 mod gate;
 mod goblin;
+mod goblin_spawner;
+mod hud;
 mod killing;
 mod level_handling;
 mod menu;
+mod plant_definitions;
 mod planting;
 mod player;
 mod player_controls;
@@ -27,14 +30,25 @@ use self::editing_helpers::EditingHelpersPlugin;
 use self::floating_text::FloatingTextPlugin;
 use self::gate::GatePlugin;
 use self::goblin::GoblinPlugin;
+use self::goblin_spawner::GoblinSpawnerPlugin;
+use self::hud::HudPlugin;
 use self::killing::KillingPlugin;
 use self::level_handling::{LevelHandlingPlugin, LevelProgress};
 use self::menu::MenuPlugin;
+use self::plant_definitions::PlantDefinitionsPlugin;
 use self::planting::PlantingPlugin;
 use self::player::PlayerPlugin;
 use self::player_controls::PlayerControlsPlugin;
 use self::shooting::ShootingPlugin;
 
+// For setting up shooters in `benches/bullets.rs`.
+pub use self::plant_definitions::BulletDefinition;
+pub use self::player_controls::ShootEvent;
+pub use self::shooting::{
+    BulletDiagnosticsPlugin, BulletPooling, CanShoot, DestroysBullets, Faction, InnateAmmunition,
+    WeaponStats, BULLETS_ACTIVE, BULLET_ENTITIES_SPAWNED,
+};
+
 pub struct GardeningGunGamePlugin {
     pub is_editor: bool,
     pub start_at_level: Option<String>,
@@ -77,10 +91,13 @@ impl Plugin for GardeningGunGamePlugin {
         app.add_plugin(AmmunitionPlugin);
         app.add_plugin(FloatingTextPlugin);
         app.add_plugin(ShootingPlugin);
+        app.add_plugin(PlantDefinitionsPlugin);
         app.add_plugin(PlantingPlugin);
         app.add_plugin(GatePlugin);
         app.add_plugin(GoblinPlugin);
+        app.add_plugin(GoblinSpawnerPlugin);
         app.add_plugin(KillingPlugin);
+        app.add_plugin(HudPlugin);
         app.add_system(enable_disable_physics);
     }
 }
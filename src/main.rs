@@ -4,6 +4,9 @@
 // Feel free to delete this line.
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
+use bevy::diagnostic::{
+    EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin,
+};
 use bevy::prelude::*;
 use bevy::window::WindowResolution;
 use bevy_egui::{EguiPlugin, EguiSettings};
@@ -14,7 +17,7 @@ use bevy_tnua::{TnuaPlatformerPlugin, TnuaRapier2dPlugin};
 use bevy_yoleck::prelude::*;
 use bevy_yoleck::vpeol::prelude::*;
 use clap::Parser;
-use gardening_gun::{GardeningGunGamePlugin, MenuActionForKbgp};
+use gardening_gun::{BulletDiagnosticsPlugin, GardeningGunGamePlugin, MenuActionForKbgp};
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -22,20 +25,37 @@ struct Args {
     editor: bool,
     #[clap(long)]
     level: Option<String>,
+    /// Log frame time, entity count and bullet pool diagnostics.
+    #[clap(long)]
+    diagnostics: bool,
 }
 
 fn main() {
     let args = Args::parse();
 
     let mut app = App::new();
-    app.add_plugins(DefaultPlugins.set(WindowPlugin {
-        primary_window: Some(Window {
-            title: "Gardening Gun".to_owned(),
-            resolution: WindowResolution::new(800.0, 600.0),
-            ..Default::default()
-        }),
-        ..Default::default()
-    }));
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Gardening Gun".to_owned(),
+                    resolution: WindowResolution::new(800.0, 600.0),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .set(AssetPlugin {
+                // For reloading the plant definitions while balancing.
+                watch_for_changes: cfg!(not(target_arch = "wasm32")),
+                ..Default::default()
+            }),
+    );
+    if args.diagnostics {
+        app.add_plugin(FrameTimeDiagnosticsPlugin);
+        app.add_plugin(EntityCountDiagnosticsPlugin);
+        app.add_plugin(LogDiagnosticsPlugin::default());
+        app.add_plugin(BulletDiagnosticsPlugin);
+    }
     app.insert_resource(PkvStore::new("AeonFelis", "GardeningGun"));
     app.add_plugin(EguiPlugin);
     app.add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
@@ -0,0 +1,143 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use serde::Deserialize;
+
+use crate::planting::PlantType;
+
+pub struct PlantDefinitionsPlugin;
+
+impl Plugin for PlantDefinitionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PlantDefinitions>();
+        app.add_asset_loader(PlantDefinitionsLoader);
+        app.init_resource::<PlantDefinitions>();
+        app.add_startup_system(load_plant_definitions);
+        app.add_system(update_plant_definitions);
+    }
+}
+
+const PLANT_DEFINITIONS_FILE: &str = "plants.ron";
+
+#[derive(TypeUuid, Resource, Deserialize, Clone)]
+#[uuid = "3b49b61f-4621-4963-a461-342e6eb52276"]
+pub struct PlantDefinitions {
+    tree: PlantDefinition,
+    vine: PlantDefinition,
+    mushroom: PlantDefinition,
+    bush: PlantDefinition,
+}
+
+// The file is also embedded in the binary, so that the definitions are usable before the asset
+// server finishes loading it.
+impl Default for PlantDefinitions {
+    fn default() -> Self {
+        ron::from_str(include_str!("../assets/plants.ron"))
+            .expect("embedded plant definitions must be valid")
+    }
+}
+
+impl PlantDefinitions {
+    pub fn get(&self, plant_type: &PlantType) -> &PlantDefinition {
+        match plant_type {
+            PlantType::Tree => &self.tree,
+            PlantType::Vine => &self.vine,
+            PlantType::Mushroom => &self.mushroom,
+            PlantType::Bush => &self.bush,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PlantDefinition {
+    pub scene: String,
+    /// Half the size of the fully grown plant. The models are built around their center.
+    pub half_extents: Vec2,
+    pub growth_pace: f32,
+    /// How long the plant lives before it wilts, unless overridden by
+    /// [`PlantsLifespan`](crate::planting::PlantsLifespan).
+    pub lifespan: Option<f32>,
+    pub shots: usize,
+    pub carried_scale: f32,
+    /// How long goblins hit by the seed stay entangled. `None` turns them into permanent
+    /// platforms.
+    pub entangle_duration: Option<f32>,
+    pub jump_boost: Option<f32>,
+    pub friction: Option<f32>,
+    pub bullet: BulletDefinition,
+    pub seed: SeedDefinition,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BulletDefinition {
+    pub speed: f32,
+    pub gravity: f32,
+    pub pierces: bool,
+    pub count: usize,
+    pub spread_degrees: f32,
+    pub ricochets: usize,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct SeedDefinition {
+    pub collider_half_height: f32,
+    pub collider_radius: f32,
+    pub eject_speed: f32,
+    pub eject_lift: f32,
+    pub spin: f32,
+    pub restitution: f32,
+    pub friction: f32,
+    pub bounces: usize,
+    pub fizzle_after: f32,
+}
+
+struct PlantDefinitionsLoader;
+
+impl AssetLoader for PlantDefinitionsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let definitions: PlantDefinitions = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(definitions));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+#[derive(Resource)]
+struct PlantDefinitionsHandle(Handle<PlantDefinitions>);
+
+fn load_plant_definitions(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(PlantDefinitionsHandle(
+        asset_server.load(PLANT_DEFINITIONS_FILE),
+    ));
+}
+
+fn update_plant_definitions(
+    mut reader: EventReader<AssetEvent<PlantDefinitions>>,
+    handle: Res<PlantDefinitionsHandle>,
+    assets: Res<Assets<PlantDefinitions>>,
+    mut plant_definitions: ResMut<PlantDefinitions>,
+) {
+    for event in reader.iter() {
+        match event {
+            AssetEvent::Created { handle: loaded } | AssetEvent::Modified { handle: loaded } => {
+                if *loaded != handle.0 {
+                    continue;
+                }
+                if let Some(loaded) = assets.get(loaded) {
+                    *plant_definitions = loaded.clone();
+                }
+            }
+            AssetEvent::Removed { .. } => {}
+        }
+    }
+}
@@ -1,8 +1,17 @@
+use std::mem::discriminant;
+
 use bevy::prelude::*;
+use bevy_egui::egui;
 use bevy_rapier2d::prelude::*;
+use bevy_tnua::{TnuaPlatformerConfig, TnuaPlatformerControls, TnuaProximitySensor};
 use bevy_yoleck::prelude::*;
+use bevy_yoleck::vpeol::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::editing_helpers::SnapToGrid;
+use crate::gate::KeepGatesClosedWhenAlive;
+use crate::plant_definitions::{PlantDefinition, PlantDefinitions, SeedDefinition};
+use crate::shooting::{DestroysBullets, DestroysBulletsOf, Faction};
 use crate::utils::events_both_ways;
 use crate::AppState;
 
@@ -10,8 +19,37 @@ pub struct PlantingPlugin;
 
 impl Plugin for PlantingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(initiate_planting);
-        app.add_system(apply_growing.in_set(OnUpdate(AppState::Game)));
+        app.add_yoleck_entity_type({
+            YoleckEntityType::new("Plant")
+                .with::<Vpeol3dPosition>()
+                .with::<PlantType>()
+                .with::<InitialGrowth>()
+                .insert_on_init(|| IsPrePlanted)
+                .insert_on_init_during_editor(|| SnapToGrid)
+        });
+        app.add_yoleck_entity_type({
+            YoleckEntityType::new("LevelSettings").with::<PlantsLifespan>()
+        });
+        app.add_yoleck_edit_system(edit_plant_type);
+        app.add_yoleck_edit_system(edit_initial_growth);
+        app.add_yoleck_edit_system(edit_soil);
+        app.add_yoleck_edit_system(edit_plants_lifespan);
+        app.yoleck_populate_schedule_mut()
+            .add_system(populate_pre_planted);
+        app.add_systems((count_seed_bounces, initiate_planting).chain());
+        app.add_systems(
+            (
+                apply_growing,
+                start_plant_lifespans,
+                wilt_plants,
+                apply_withering,
+                launch_from_bouncy_plants,
+                entangle_characters,
+                release_entangled,
+                fizzle_seeds,
+            )
+                .in_set(OnUpdate(AppState::Game)),
+        );
     }
 }
 
@@ -19,67 +57,688 @@ impl Plugin for PlantingPlugin {
 pub enum PlantType {
     #[default]
     Tree,
+    Vine,
+    Mushroom,
+    Bush,
 }
 
 impl PlantType {
-    pub fn scene_name(&self) -> &'static str {
+    pub const ALL: [PlantType; 4] = [
+        PlantType::Tree,
+        PlantType::Vine,
+        PlantType::Mushroom,
+        PlantType::Bush,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlantType::Tree => "Tree",
+            PlantType::Vine => "Vine",
+            PlantType::Mushroom => "Mushroom",
+            PlantType::Bush => "Bush",
+        }
+    }
+}
+
+fn edit_plant_type(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut PlantType>) {
+    let Ok(mut plant_type) = edit.get_single_mut() else { return };
+    ui.horizontal(|ui| {
+        for option in PlantType::ALL {
+            let name = option.name();
+            ui.selectable_value(plant_type.as_mut(), option, name);
+        }
+    });
+}
+
+#[derive(YoleckComponent, Default, Clone, PartialEq, Component, Serialize, Deserialize)]
+pub enum PlantsLifespan {
+    #[default]
+    ByPlantType,
+    Permanent,
+    WitherAfter(f32),
+}
+
+impl PlantsLifespan {
+    fn for_plant(&self, definition: &PlantDefinition) -> Option<f32> {
+        match self {
+            PlantsLifespan::ByPlantType => definition.lifespan,
+            PlantsLifespan::Permanent => None,
+            PlantsLifespan::WitherAfter(seconds) => Some(*seconds),
+        }
+    }
+}
+
+fn edit_plants_lifespan(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut PlantsLifespan>) {
+    let Ok(mut plants_lifespan) = edit.get_single_mut() else { return };
+    ui.label("Plants Lifespan:");
+    ui.horizontal(|ui| {
+        for (label, option) in [
+            ("By Plant Type", PlantsLifespan::ByPlantType),
+            ("Permanent", PlantsLifespan::Permanent),
+            ("Wither After", PlantsLifespan::WitherAfter(10.0)),
+        ] {
+            let selected = discriminant(plants_lifespan.as_ref()) == discriminant(&option);
+            if ui.selectable_label(selected, label).clicked() && !selected {
+                *plants_lifespan = option;
+            }
+        }
+    });
+    if let PlantsLifespan::WitherAfter(seconds) = plants_lifespan.as_mut() {
+        ui.add(egui::Slider::new(seconds, 1.0..=120.0).suffix(" seconds"));
+    }
+}
+
+#[derive(Component)]
+struct IsPrePlanted;
+
+/// The scale a pre-planted plant starts with. Plants that are not fully grown will keep growing
+/// when the level starts.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Component, YoleckComponent)]
+#[serde(transparent)]
+pub struct InitialGrowth(pub f32);
+
+impl Default for InitialGrowth {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+fn edit_initial_growth(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut InitialGrowth>) {
+    let Ok(mut initial_growth) = edit.get_single_mut() else { return };
+    ui.add(egui::Slider::new(&mut initial_growth.0, 0.1..=1.0).prefix("Initial Growth: "));
+}
+
+#[derive(Component)]
+struct PopulatedModel(PlantType, f32);
+
+fn populate_pre_planted(
+    mut populate: YoleckPopulate<
+        (
+            &Vpeol3dPosition,
+            &PlantType,
+            &InitialGrowth,
+            Option<&PopulatedModel>,
+        ),
+        With<IsPrePlanted>,
+    >,
+    asset_server: Res<AssetServer>,
+    plant_definitions: Res<PlantDefinitions>,
+    marking: YoleckMarking,
+) {
+    populate.populate(|ctx, mut cmd, (position, plant_type, growth, populated)| {
+        let definition = plant_definitions.get(plant_type);
+        // The position is of the grid cell the plant grows from, so the plant's bottom is at the
+        // bottom of that cell.
+        let offset_from_cell = Vec3::Y * (definition.half_extents.y * growth.0 - 0.5);
+        if !ctx.is_in_editor() {
+            spawn_plant(
+                cmd.commands(),
+                &asset_server,
+                &plant_definitions,
+                plant_type,
+                Transform::from_translation(position.0 + offset_from_cell)
+                    .with_scale(Vec3::ONE * growth.0),
+                1.0,
+            );
+            return;
+        }
+        let model_up_to_date = populated.is_some_and(|PopulatedModel(model_type, model_growth)| {
+            model_type == plant_type && *model_growth == growth.0
+        });
+        if !model_up_to_date {
+            marking.despawn_marked(&mut cmd);
+            cmd.insert(VpeolWillContainClickableChildren);
+            cmd.insert(VisibilityBundle::default());
+            cmd.with_children(|commands| {
+                let mut child = commands.spawn(marking.marker());
+                child.insert(SceneBundle {
+                    scene: asset_server.load(definition.scene.as_str()),
+                    transform: Transform::from_translation(offset_from_cell)
+                        .with_scale(Vec3::ONE * growth.0),
+                    ..Default::default()
+                });
+            });
+            cmd.insert(PopulatedModel(plant_type.clone(), growth.0));
+        }
+    });
+}
+
+#[derive(YoleckComponent, Default, Clone, PartialEq, Eq, Component, Serialize, Deserialize)]
+pub enum Soil {
+    #[default]
+    Fertile,
+    Barren,
+    Rich,
+    Poisoned,
+}
+
+impl Soil {
+    pub const ALL: [Soil; 4] = [Soil::Fertile, Soil::Barren, Soil::Rich, Soil::Poisoned];
+
+    pub fn name(&self) -> &'static str {
         match self {
-            PlantType::Tree => "Tree.glb#Scene0",
+            Soil::Fertile => "Fertile",
+            Soil::Barren => "Barren",
+            Soil::Rich => "Rich",
+            Soil::Poisoned => "Poisoned",
         }
     }
 }
 
+fn edit_soil(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut Soil>) {
+    let Ok(mut soil) = edit.get_single_mut() else { return };
+    ui.horizontal(|ui| {
+        for option in Soil::ALL {
+            let name = option.name();
+            ui.selectable_value(soil.as_mut(), option, name);
+        }
+    });
+}
+
 #[derive(Component)]
-pub struct FertileGround;
+pub struct FlyingSeed {
+    bounces_left: usize,
+    fizzle_timer: Timer,
+}
 
+impl FlyingSeed {
+    pub fn new(definition: &SeedDefinition) -> Self {
+        Self {
+            bounces_left: definition.bounces,
+            fizzle_timer: Timer::from_seconds(definition.fizzle_after, TimerMode::Once),
+        }
+    }
+}
+
+const SEED_REST_SPEED: f32 = 1.0;
+
+/// Plants that were planted or pre-planted (as opposed to seeds and ammunition)
 #[derive(Component)]
-pub struct FlyingSeed;
+pub struct Planted;
 
 #[derive(Component)]
-struct Growing;
+struct Lifespan(Timer);
 
-fn initiate_planting(
+#[derive(Component)]
+pub struct Growing {
+    target_scale: f32,
+}
+
+#[derive(Component)]
+pub struct Withering {
+    pub pace: f32,
+}
+
+/// Characters that growing plants push in their growth direction, rather than treat as
+/// obstructions.
+#[derive(Component)]
+pub struct LiftedByGrowingPlants;
+
+#[derive(Component)]
+pub struct Climbable;
+
+#[derive(Component)]
+pub struct Bouncy {
+    jump_boost: f32,
+}
+
+/// Added to characters that were launched by a [`Bouncy`] plant, until they reach the top of the
+/// launch.
+#[derive(Component)]
+pub struct LaunchedByPlant {
+    pub jump_boost: f32,
+}
+
+#[derive(Component)]
+pub struct Entanglable;
+
+#[derive(Component)]
+pub struct Entangled {
+    timer: Option<Timer>,
+    vines: Vec<Entity>,
+}
+
+impl Entangled {
+    /// Permanently entangled characters are out of the fight for good.
+    pub fn is_permanent(&self) -> bool {
+        self.timer.is_none()
+    }
+}
+
+/// The direction a plant grows in when planted on a surface with the given normal.
+///
+/// Blocks are axis aligned, so the normal is snapped to the nearest axis to avoid slanted plants
+/// when seeds hit corners.
+fn growth_direction(surface_normal: Vec2) -> Vec2 {
+    if surface_normal.y.abs() < surface_normal.x.abs() {
+        Vec2::X * surface_normal.x.signum()
+    } else {
+        Vec2::Y * surface_normal.y.signum()
+    }
+}
+
+fn count_seed_bounces(
     mut reader: EventReader<CollisionEvent>,
-    seed_query: Query<(&GlobalTransform, &PlantType), With<FlyingSeed>>,
-    ground_query: Query<(), With<FertileGround>>,
+    mut seed_query: Query<&mut FlyingSeed>,
+    sensors_query: Query<(), With<Sensor>>,
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
 ) {
     for (e1, e2) in events_both_ways(&mut reader) {
-        let (Ok((transform, plant_type)), Ok(_)) = (seed_query.get(e1), ground_query.get(e2)) else { continue };
-        commands.entity(e1).despawn_recursive();
-        let mut cmd = commands.spawn_empty();
-        cmd.insert(plant_type.clone());
-        cmd.insert(SceneBundle {
-            scene: asset_server.load(plant_type.scene_name()),
-            transform: Transform::from_translation(transform.translation() + Vec3::Y)
-                .with_scale(Vec3::ONE * 0.1),
-            ..Default::default()
-        });
+        let Ok(mut seed) = seed_query.get_mut(e1) else { continue };
+        if seed.bounces_left == 0 || sensors_query.contains(e2) {
+            continue;
+        }
+        seed.bounces_left -= 1;
+        if seed.bounces_left == 0 {
+            // Out of bounces - let the seed roll to a stop.
+            commands.entity(e1).insert((
+                Restitution {
+                    coefficient: 0.0,
+                    combine_rule: CoefficientCombineRule::Min,
+                },
+                Damping {
+                    linear_damping: 1.0,
+                    angular_damping: 3.0,
+                },
+            ));
+        }
+    }
+}
+
+fn initiate_planting(
+    seed_query: Query<(Entity, &GlobalTransform, &PlantType, &Velocity), With<FlyingSeed>>,
+    ground_query: Query<(&GlobalTransform, &Soil)>,
+    rapier_context: Res<RapierContext>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    plant_definitions: Res<PlantDefinitions>,
+) {
+    for (seed_entity, transform, plant_type, velocity) in seed_query.iter() {
+        for contact_pair in rapier_context.contacts_with(seed_entity) {
+            if !contact_pair.has_any_active_contacts() {
+                continue;
+            }
+            let seed_is_first = contact_pair.collider1() == seed_entity;
+            let ground_entity = if seed_is_first {
+                contact_pair.collider2()
+            } else {
+                contact_pair.collider1()
+            };
+            let Ok((ground_transform, soil)) = ground_query.get(ground_entity) else { continue };
+            let target_scale = match soil {
+                Soil::Fertile => 1.0,
+                Soil::Barren => {
+                    continue;
+                }
+                Soil::Rich => 1.5,
+                Soil::Poisoned => {
+                    let mut cmd = commands.entity(seed_entity);
+                    cmd.remove::<FlyingSeed>();
+                    cmd.insert(Withering { pace: 1.0 });
+                    break;
+                }
+            };
+            // The manifold's normal points from the first collider to the second.
+            let surface_normal = contact_pair
+                .manifolds()
+                .next()
+                .map(|manifold| {
+                    if seed_is_first {
+                        -manifold.normal()
+                    } else {
+                        manifold.normal()
+                    }
+                })
+                .filter(|normal| 0.0 < normal.length_squared())
+                .unwrap_or_else(|| {
+                    (transform.translation() - ground_transform.translation()).truncate()
+                });
+            let growth_direction = growth_direction(surface_normal);
+            // Seeds plant in walls and ceilings on impact, but on the floor they first roll to a
+            // stop.
+            if growth_direction == Vec2::Y && SEED_REST_SPEED < velocity.linvel.length() {
+                continue;
+            }
+
+            commands.entity(seed_entity).despawn_recursive();
+            spawn_plant(
+                &mut commands,
+                &asset_server,
+                &plant_definitions,
+                plant_type,
+                Transform::from_translation(transform.translation() + growth_direction.extend(0.0))
+                    .with_rotation(Quat::from_rotation_arc_2d(Vec2::Y, growth_direction))
+                    .with_scale(Vec3::ONE * 0.1),
+                target_scale,
+            );
+            break;
+        }
+    }
+}
+
+/// Spawn a plant that will keep growing from the scale in `transform` until it reaches
+/// `target_scale`. The plant grows toward the transform's up direction.
+pub fn spawn_plant(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    plant_definitions: &PlantDefinitions,
+    plant_type: &PlantType,
+    transform: Transform,
+    target_scale: f32,
+) {
+    let definition = plant_definitions.get(plant_type);
+    let rests_on_ground = 0.5 < (transform.rotation * Vec3::Y).y;
+    let mut cmd = commands.spawn_empty();
+    cmd.insert(plant_type.clone());
+    cmd.insert(SceneBundle {
+        scene: asset_server.load(definition.scene.as_str()),
+        transform,
+        ..Default::default()
+    });
+    let half_extents = definition.half_extents;
+    cmd.insert(Collider::cuboid(half_extents.x, half_extents.y));
+    // Only plants that stand on the ground can rest on it - plants that grow from walls and
+    // ceilings must hold on to them.
+    if rests_on_ground && !matches!(plant_type, PlantType::Vine) {
         cmd.insert(RigidBody::Dynamic);
-        cmd.insert(Collider::cuboid(1.0, 1.5));
         cmd.insert(LockedAxes::ROTATION_LOCKED | LockedAxes::TRANSLATION_LOCKED_X);
-        cmd.insert(Growing);
-        cmd.insert(YoleckBelongsToLevel);
-        cmd.insert(SolverGroups {
-            memberships: crate::solver_groups::PLANTED,
-            filters: Group::ALL,
-        });
+    } else {
+        cmd.insert(RigidBody::Fixed);
+    }
+    match plant_type {
+        PlantType::Tree => {
+            // Trees shield the player, but do not get in the way of their own shots.
+            cmd.insert(DestroysBulletsOf(Faction::Goblins));
+        }
+        PlantType::Vine => {
+            // Vines are not solid, so they must be fixed or they'll fall through the ground.
+            cmd.insert(Sensor);
+            cmd.insert(Climbable);
+        }
+        PlantType::Mushroom => {}
+        PlantType::Bush => {
+            cmd.insert(DestroysBullets);
+        }
+    }
+    if let Some(jump_boost) = definition.jump_boost {
+        cmd.insert(Bouncy { jump_boost });
+    }
+    if let Some(friction) = definition.friction {
+        cmd.insert(Friction::coefficient(friction));
     }
+    if transform.scale.x < target_scale {
+        cmd.insert(Growing { target_scale });
+    }
+    cmd.insert(Planted);
+    cmd.insert(YoleckBelongsToLevel);
+    cmd.insert(SolverGroups {
+        memberships: crate::solver_groups::PLANTED,
+        filters: Group::ALL,
+    });
 }
 
+#[allow(clippy::type_complexity)]
 fn apply_growing(
     time: Res<Time>,
-    mut query: Query<(Entity, &mut Transform), With<Growing>>,
+    rapier_context: Res<RapierContext>,
+    plant_definitions: Res<PlantDefinitions>,
+    mut query: Query<(Entity, &PlantType, &Growing, &mut Transform)>,
+    mut lifted_query: Query<
+        (&mut Transform, &mut Velocity),
+        (With<LiftedByGrowingPlants>, Without<Growing>),
+    >,
     mut commands: Commands,
 ) {
-    for (entity, mut transform) in query.iter_mut() {
-        let pace = 2.0 * time.delta_seconds();
-        transform.scale += Vec3::ONE * pace;
-        transform.translation += Vec3::Y * pace;
-        if 1.0 <= transform.scale.length_squared() {
-            transform.scale = Vec3::ONE;
+    for (entity, plant_type, growing, mut transform) in query.iter_mut() {
+        let definition = plant_definitions.get(plant_type);
+        // The last step only grows what's left, so it goes through the same checks as the others.
+        let pace = (definition.growth_pace * time.delta_seconds())
+            .min(growing.target_scale - transform.scale.x);
+        let full_half_extents = definition.half_extents;
+        let half_extents = full_half_extents * transform.scale.truncate();
+        let position = transform.translation.truncate();
+        // Plants planted on walls and ceilings are rotated, so all the calculations are done in
+        // the plant's local axes.
+        let rotation = transform.rotation;
+        let (_, _, angle) = rotation.to_euler(EulerRot::XYZ);
+        let to_world = |local: Vec2| (rotation * local.extend(0.0)).truncate();
+
+        let is_obstruction = |other: Entity| !lifted_query.contains(other);
+        let filter = QueryFilter::new()
+            .exclude_rigid_body(entity)
+            .exclude_sensors()
+            .predicate(&is_obstruction);
+
+        // The casted slabs start a bit inside the plant, so that the ground it stands on (or the
+        // wall it leans on) is not mistaken for something it is about to grow into.
+        let room_in_direction = |direction: Vec2, wanted: f32| {
+            let slab_half_extents = Vec2::splat(0.9) - 0.8 * direction.abs();
+            let slab = Collider::cuboid(
+                slab_half_extents.x * half_extents.x,
+                slab_half_extents.y * half_extents.y,
+            );
+            let origin = position + to_world(0.9 * direction * half_extents);
+            if let Some((_, toi)) =
+                rapier_context.cast_shape(origin, angle, to_world(direction), &slab, wanted, filter)
+            {
+                toi.toi
+            } else {
+                wanted
+            }
+        };
+        let wanted_rise = 2.0 * full_half_extents.y * pace;
+        let wanted_widening = full_half_extents.x * pace;
+        let room_above = room_in_direction(Vec2::Y, wanted_rise);
+        let room_left = room_in_direction(-Vec2::X, wanted_widening);
+        let room_right = room_in_direction(Vec2::X, wanted_widening);
+
+        let actual_pace = pace
+            .min(room_above / (2.0 * full_half_extents.y))
+            .min((room_left + room_right) / (2.0 * full_half_extents.x))
+            .max(0.0);
+        let widening = full_half_extents.x * actual_pace;
+        // When blocked on one side, grow away from it.
+        let sideways_shift = (widening - room_left).max(0.0) - (widening - room_right).max(0.0);
+
+        let old_top = position + to_world(Vec2::Y * half_extents.y);
+        let rise = 2.0 * full_half_extents.y * actual_pace;
+        transform.scale += Vec3::ONE * actual_pace;
+        transform.translation +=
+            to_world(Vec2::new(sideways_shift, full_half_extents.y * actual_pace)).extend(0.0);
+
+        let growth_direction = to_world(Vec2::Y);
+        let mut to_lift = Vec::new();
+        rapier_context.intersections_with_shape(
+            old_top + to_world(Vec2::new(sideways_shift, 0.5 * rise)),
+            angle,
+            &Collider::cuboid(half_extents.x + widening, 0.5 * rise),
+            QueryFilter::new()
+                .exclude_rigid_body(entity)
+                .exclude_sensors(),
+            |other| {
+                if lifted_query.contains(other) {
+                    to_lift.push(other);
+                }
+                true
+            },
+        );
+        for lifted_entity in to_lift {
+            let Ok((mut lifted_transform, mut lifted_velocity)) = lifted_query.get_mut(lifted_entity) else { continue };
+            lifted_transform.translation += (rise * growth_direction).extend(0.0);
+            let growth_speed = rise / time.delta_seconds();
+            let speed_in_growth_direction = lifted_velocity.linvel.dot(growth_direction);
+            if speed_in_growth_direction < growth_speed {
+                lifted_velocity.linvel +=
+                    (growth_speed - speed_in_growth_direction) * growth_direction;
+            }
+        }
+
+        if growing.target_scale <= transform.scale.x {
             commands.entity(entity).remove::<Growing>();
+        } else if actual_pace < pace {
+            // Blocked - the plant remains stunted.
+            commands.entity(entity).remove::<Growing>();
+        }
+    }
+}
+
+fn start_plant_lifespans(
+    query: Query<(Entity, &PlantType), Added<Planted>>,
+    settings_query: Query<&PlantsLifespan>,
+    plant_definitions: Res<PlantDefinitions>,
+    mut commands: Commands,
+) {
+    let plants_lifespan = settings_query.get_single().cloned().unwrap_or_default();
+    for (entity, plant_type) in query.iter() {
+        if let Some(lifespan) = plants_lifespan.for_plant(plant_definitions.get(plant_type)) {
+            commands
+                .entity(entity)
+                .insert(Lifespan(Timer::from_seconds(lifespan, TimerMode::Once)));
+        }
+    }
+}
+
+fn wilt_plants(time: Res<Time>, mut query: Query<(Entity, &mut Lifespan)>, mut commands: Commands) {
+    for (entity, mut lifespan) in query.iter_mut() {
+        if lifespan.0.tick(time.delta()).finished() {
+            let mut cmd = commands.entity(entity);
+            cmd.remove::<(Lifespan, Growing)>();
+            cmd.insert(Withering { pace: 0.5 });
+        }
+    }
+}
+
+fn apply_withering(
+    time: Res<Time>,
+    mut query: Query<(Entity, &Withering, &mut Transform)>,
+    mut commands: Commands,
+) {
+    for (entity, withering, mut transform) in query.iter_mut() {
+        transform.scale -= Vec3::ONE * withering.pace * time.delta_seconds();
+        if transform.scale.min_element() <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn launch_from_bouncy_plants(
+    query: Query<(
+        Entity,
+        &TnuaProximitySensor,
+        &TnuaPlatformerConfig,
+        &Velocity,
+        Option<&LaunchedByPlant>,
+    )>,
+    bouncy_query: Query<&Bouncy>,
+    mut commands: Commands,
+) {
+    for (entity, sensor, config, velocity, launched) in query.iter() {
+        let bouncy = sensor.output.as_ref().and_then(|output| {
+            if output.proximity <= config.float_height + config.cling_distance {
+                bouncy_query.get(output.entity).ok()
+            } else {
+                None
+            }
+        });
+        if let Some(bouncy) = bouncy {
+            commands.entity(entity).insert(LaunchedByPlant {
+                jump_boost: bouncy.jump_boost,
+            });
+        } else if launched.is_some() && velocity.linvel.y <= 0.0 {
+            commands.entity(entity).remove::<LaunchedByPlant>();
+        }
+    }
+}
+
+fn entangle_characters(
+    mut reader: EventReader<CollisionEvent>,
+    seed_query: Query<&PlantType, With<FlyingSeed>>,
+    mut query: Query<Option<&mut Entangled>, With<Entanglable>>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    plant_definitions: Res<PlantDefinitions>,
+) {
+    for (e1, e2) in events_both_ways(&mut reader) {
+        let (Ok(plant_type), Ok(entangled)) = (seed_query.get(e1), query.get_mut(e2)) else { continue };
+        commands.entity(e1).despawn_recursive();
+        let timer = plant_definitions
+            .get(plant_type)
+            .entangle_duration
+            .map(|duration| Timer::from_seconds(duration, TimerMode::Once));
+        let mut cmd = commands.entity(e2);
+        if let Some(mut entangled) = entangled {
+            if entangled.timer.is_none() {
+                // Already a platform - nothing more to do.
+                continue;
+            }
+            entangled.timer = timer.clone();
+        } else {
+            let vine_definition = plant_definitions.get(&PlantType::Vine);
+            let vine_scene = asset_server.load(vine_definition.scene.as_str());
+            let mut vines = Vec::new();
+            cmd.with_children(|commands| {
+                for angle in [-0.4, 0.4] {
+                    vines.push(
+                        commands
+                            .spawn(SceneBundle {
+                                scene: vine_scene.clone(),
+                                transform: Transform::from_rotation(Quat::from_rotation_z(angle))
+                                    .with_scale(Vec3::new(1.0, 0.25, 1.0)),
+                                ..Default::default()
+                            })
+                            .id(),
+                    );
+                }
+            });
+            cmd.insert(LockedAxes::ROTATION_LOCKED | LockedAxes::TRANSLATION_LOCKED);
+            // The AI skips entangled characters, so whatever it last set would stay in effect.
+            cmd.insert(TnuaPlatformerControls::default());
+            cmd.insert(Entangled {
+                timer: timer.clone(),
+                vines,
+            });
+        }
+        if timer.is_none() {
+            // Permanently entangled characters are out of the fight, and can be stood on like
+            // planted plants.
+            cmd.remove::<KeepGatesClosedWhenAlive>();
+            cmd.insert(SolverGroups {
+                memberships: crate::solver_groups::PLANTED,
+                filters: Group::ALL,
+            });
+        }
+    }
+}
+
+fn release_entangled(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Entangled)>,
+    mut commands: Commands,
+) {
+    for (entity, mut entangled) in query.iter_mut() {
+        let Some(timer) = entangled.timer.as_mut() else { continue };
+        if !timer.tick(time.delta()).finished() {
+            continue;
+        }
+        for vine in entangled.vines.iter() {
+            commands.entity(*vine).despawn_recursive();
+        }
+        let mut cmd = commands.entity(entity);
+        cmd.remove::<Entangled>();
+        cmd.insert(LockedAxes::ROTATION_LOCKED);
+    }
+}
+
+fn fizzle_seeds(
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut FlyingSeed)>,
+    mut commands: Commands,
+) {
+    for (entity, mut seed) in query.iter_mut() {
+        if seed.fizzle_timer.tick(time.delta()).finished() {
+            let mut cmd = commands.entity(entity);
+            cmd.remove::<FlyingSeed>();
+            cmd.insert(Withering { pace: 1.0 });
         }
     }
 }
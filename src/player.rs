@@ -14,8 +14,9 @@ use bevy_yoleck::vpeol::prelude::*;
 use crate::ammunition::{CanCarry, CanPick};
 use crate::animating::{AnimationsOwner, ApplyRotationToChild, GetClipsFrom};
 use crate::editing_helpers::SnapToGrid;
-use crate::killing::Killable;
-use crate::shooting::CanShoot;
+use crate::killing::Health;
+use crate::planting::LiftedByGrowingPlants;
+use crate::shooting::{CanShoot, DestroysBullets, Faction, WeaponStats};
 use crate::AppState;
 
 pub struct PlayerPlugin;
@@ -25,6 +26,7 @@ impl Plugin for PlayerPlugin {
         app.add_yoleck_entity_type({
             YoleckEntityType::new("Player")
                 .with::<Vpeol3dPosition>()
+                .with::<WeaponStats>()
                 .insert_on_init(|| IsPlayer)
                 .insert_on_init(|| Vpeol3dRotatation(Quat::from_rotation_y(PI)))
                 .insert_on_init_during_editor(|| SnapToGrid)
@@ -38,6 +40,8 @@ impl Plugin for PlayerPlugin {
 #[derive(Component)]
 pub struct IsPlayer;
 
+const PLAYER_MAX_HEALTH: u32 = 3;
+
 fn populate_player(
     mut populate: YoleckPopulate<(), With<IsPlayer>>,
     asset_server: Res<AssetServer>,
@@ -95,11 +99,15 @@ fn populate_player(
         cmd.insert(CanPick);
         cmd.insert(CanCarry::default());
         cmd.insert(CanShoot::default());
+        cmd.insert(Faction::Player);
+        // Only stops the goblins' bullets, since bullets pass through their own faction.
+        cmd.insert(DestroysBullets);
         cmd.insert(SolverGroups {
             memberships: crate::solver_groups::PLAYER,
             filters: crate::solver_groups::PLANTED,
         });
-        cmd.insert(Killable::default());
+        cmd.insert(Health::new(PLAYER_MAX_HEALTH));
+        cmd.insert(LiftedByGrowingPlants);
         cmd.insert(TnuaAnimatingState::<PlayerAnimationState>::default());
         cmd.insert(TnuaPlatformerAnimatingOutput::default());
     });
@@ -116,13 +124,13 @@ fn animate_player(
     mut query: Query<(
         &mut TnuaAnimatingState<PlayerAnimationState>,
         &TnuaPlatformerAnimatingOutput,
-        &Killable,
+        &Health,
         &AnimationsOwner,
     )>,
     mut animation_players_query: Query<&mut AnimationPlayer>,
 ) {
-    for (mut animating_state, animating_output, killable, animations_owner) in query.iter_mut() {
-        if !killable.still_alive {
+    for (mut animating_state, animating_output, health, animations_owner) in query.iter_mut() {
+        if !health.is_alive() {
             // Death animation is handled elsewhere
             continue;
         }
@@ -1,9 +1,15 @@
+use std::f32::consts::FRAC_PI_2;
+
 use bevy::prelude::*;
-use bevy_tnua::TnuaPlatformerControls;
+use bevy::window::PrimaryWindow;
+use bevy_rapier2d::prelude::*;
+use bevy_tnua::{TnuaPlatformerControls, TnuaSystemSet};
 use bevy_yoleck::prelude::*;
 use leafwing_input_manager::prelude::*;
 
+use crate::planting::{Climbable, LaunchedByPlant};
 use crate::player::IsPlayer;
+use crate::shooting::{CanShoot, WeaponStats};
 use crate::AppState;
 
 #[derive(Actionlike, Clone, Debug)]
@@ -11,6 +17,12 @@ enum PlayerAction {
     Run,
     Jump,
     Shoot,
+    Harvest,
+    Aim,
+    /// While held, the Run input aims instead of running.
+    AimLock,
+    CycleAmmunition,
+    DropAmmunition,
 }
 
 pub struct PlayerControlsPlugin;
@@ -18,18 +30,62 @@ pub struct PlayerControlsPlugin;
 impl Plugin for PlayerControlsPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ShootEvent>();
+        app.add_event::<HarvestEvent>();
+        app.add_event::<CycleAmmunitionEvent>();
+        app.add_event::<DropAmmunitionEvent>();
         app.add_plugin(InputManagerPlugin::<PlayerAction>::default());
         app.yoleck_populate_schedule_mut()
             .add_system(add_controls_to_player);
-        app.add_system(apply_controls.in_set(OnUpdate(AppState::Game)));
+        app.add_systems(
+            (update_aiming, apply_controls)
+                .chain()
+                .in_set(OnUpdate(AppState::Game)),
+        );
+        app.add_system(
+            apply_climbing
+                .after(TnuaSystemSet)
+                .in_set(OnUpdate(AppState::Game)),
+        );
     }
 }
 
-fn add_controls_to_player(mut populate: YoleckPopulate<(), With<IsPlayer>>) {
+fn add_controls_to_player(
+    mut populate: YoleckPopulate<(), With<IsPlayer>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
     populate.populate(|ctx, mut cmd, ()| {
         if ctx.is_in_editor() {
             return;
         }
+        if ctx.is_first_time() {
+            let mut reticle = cmd.commands().spawn(PbrBundle {
+                mesh: meshes.add(
+                    shape::Torus {
+                        radius: 0.3,
+                        ring_radius: 0.06,
+                        subdivisions_segments: 16,
+                        subdivisions_sides: 8,
+                    }
+                    .into(),
+                ),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::YELLOW,
+                    unlit: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+            reticle.insert(AimReticle);
+            let reticle = reticle.id();
+            cmd.add_child(reticle);
+            cmd.insert(Aiming {
+                direction: None,
+                using_mouse: false,
+                held: false,
+                reticle,
+            });
+        }
         cmd.insert(InputManagerBundle::<PlayerAction> {
             action_state: Default::default(),
             input_map: {
@@ -48,27 +104,173 @@ fn add_controls_to_player(mut populate: YoleckPopulate<(), With<IsPlayer>>) {
                 input_map.insert(KeyCode::K, PlayerAction::Shoot);
                 input_map.insert(GamepadButtonType::West, PlayerAction::Shoot);
 
+                input_map.insert(KeyCode::C, PlayerAction::Harvest);
+                input_map.insert(KeyCode::L, PlayerAction::Harvest);
+                input_map.insert(GamepadButtonType::North, PlayerAction::Harvest);
+
+                input_map.insert(DualAxis::right_stick(), PlayerAction::Aim);
+
+                input_map.insert(KeyCode::LShift, PlayerAction::AimLock);
+                input_map.insert(KeyCode::RShift, PlayerAction::AimLock);
+                input_map.insert(GamepadButtonType::LeftTrigger, PlayerAction::AimLock);
+
+                input_map.insert(KeyCode::V, PlayerAction::CycleAmmunition);
+                input_map.insert(KeyCode::Semicolon, PlayerAction::CycleAmmunition);
+                input_map.insert(
+                    GamepadButtonType::RightTrigger,
+                    PlayerAction::CycleAmmunition,
+                );
+
+                input_map.insert(KeyCode::B, PlayerAction::DropAmmunition);
+                input_map.insert(KeyCode::Apostrophe, PlayerAction::DropAmmunition);
+                input_map.insert(GamepadButtonType::East, PlayerAction::DropAmmunition);
+
                 input_map
             },
         });
+        cmd.insert(GravityScale(1.0));
     });
 }
 
+#[derive(Component)]
+pub struct Aiming {
+    /// `None` means aiming forward.
+    pub direction: Option<Vec2>,
+    /// Once the mouse moves, the player aims at the cursor until aiming with other inputs.
+    using_mouse: bool,
+    /// Whether an aim input (the aim stick or Aim Lock) is held right now. The cursor alone does
+    /// not count, since it always points somewhere.
+    held: bool,
+    reticle: Entity,
+}
+
+#[derive(Component)]
+struct AimReticle;
+
+/// How far from the player's center the reticle is shown.
+const RETICLE_DISTANCE: f32 = 2.0;
+
+/// How long the Shoot action needs to be held for a charged shot.
+const FULL_CHARGE_DURATION: f32 = 1.0;
+
+/// How much of a full charge was gathered. Reaches 1.0 when fully charged.
+fn shot_charge(input: &ActionState<PlayerAction>) -> f32 {
+    let held_duration = if input.pressed(PlayerAction::Shoot) {
+        input.current_duration(PlayerAction::Shoot)
+    } else if input.just_released(PlayerAction::Shoot) {
+        input.previous_duration(PlayerAction::Shoot)
+    } else {
+        return 0.0;
+    };
+    (held_duration.as_secs_f32() / FULL_CHARGE_DURATION).min(1.0)
+}
+
+#[allow(clippy::type_complexity)]
+fn update_aiming(
+    mut query: Query<(
+        &ActionState<PlayerAction>,
+        &TnuaPlatformerControls,
+        &GlobalTransform,
+        &CanShoot,
+        &WeaponStats,
+        &mut Aiming,
+    )>,
+    mut reticle_query: Query<&mut Transform, With<AimReticle>>,
+    mut cursor_moved_reader: EventReader<CursorMoved>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+) {
+    let mouse_moved = cursor_moved_reader.iter().last().is_some();
+    let cursor_ray = window_query
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(|cursor_position| {
+            let (camera, camera_transform) = camera_query.get_single().ok()?;
+            camera.viewport_to_world(camera_transform, cursor_position)
+        });
+    for (input, controls, transform, can_shoot, weapon_stats, mut aiming) in query.iter_mut() {
+        let shooting_height = can_shoot.current_stats(weapon_stats).spawn_offset.y;
+        let aim_origin = transform.translation() + shooting_height * Vec3::Y;
+        let stick_aim = input
+            .clamped_axis_pair(PlayerAction::Aim)
+            .map(|axis_pair| axis_pair.xy())
+            .filter(|aim| 0.3 < aim.length());
+        let locked_aim = input
+            .clamped_axis_pair(PlayerAction::Run)
+            .map(|axis_pair| axis_pair.xy())
+            .filter(|aim| input.pressed(PlayerAction::AimLock) && 0.1 < aim.length());
+        aiming.held = stick_aim.is_some() || input.pressed(PlayerAction::AimLock);
+        if let Some(aim) = stick_aim.or(locked_aim) {
+            aiming.using_mouse = false;
+            aiming.direction = Some(aim.normalize());
+        } else if mouse_moved || aiming.using_mouse {
+            aiming.using_mouse = true;
+            aiming.direction = cursor_ray.and_then(|ray| {
+                let distance = ray.intersect_plane(aim_origin, Vec3::Z)?;
+                (ray.get_point(distance) - aim_origin)
+                    .truncate()
+                    .try_normalize()
+            });
+        } else {
+            aiming.direction = None;
+        }
+
+        let Ok(mut reticle_transform) = reticle_query.get_mut(aiming.reticle) else { continue };
+        let direction = aiming
+            .direction
+            .unwrap_or_else(|| controls.desired_forward.truncate());
+        *reticle_transform = Transform::from_translation(
+            (RETICLE_DISTANCE * direction).extend(0.0) + shooting_height * Vec3::Y,
+        )
+        .with_rotation(Quat::from_rotation_x(FRAC_PI_2))
+        // The reticle doubles as the charge and cooldown indicator.
+        .with_scale(
+            Vec3::ONE * (0.5 + 0.5 * can_shoot.cooldown_progress()) * (1.0 + shot_charge(input)),
+        );
+    }
+}
+
 pub struct ShootEvent {
     pub shooter_entity: Entity,
     pub direction: Vec3,
+    pub charged: bool,
+}
+
+pub struct HarvestEvent {
+    pub harvester_entity: Entity,
 }
 
+pub struct CycleAmmunitionEvent {
+    pub carrier_entity: Entity,
+}
+
+pub struct DropAmmunitionEvent {
+    pub carrier_entity: Entity,
+    pub direction: Vec3,
+    /// Thrown ammunition flies in the aimed direction instead of being dropped in front. It is
+    /// thrown when dropped while an aim input is held.
+    pub thrown: bool,
+}
+
+#[allow(clippy::type_complexity)]
 fn apply_controls(
     mut query: Query<(
         Entity,
         &ActionState<PlayerAction>,
         &mut TnuaPlatformerControls,
+        Option<&LaunchedByPlant>,
+        &Aiming,
     )>,
     mut shoot_events_writer: EventWriter<ShootEvent>,
+    mut harvest_events_writer: EventWriter<HarvestEvent>,
+    mut cycle_ammunition_events_writer: EventWriter<CycleAmmunitionEvent>,
+    mut drop_ammunition_events_writer: EventWriter<DropAmmunitionEvent>,
 ) {
-    for (player_entity, input, mut controls) in query.iter_mut() {
-        if let Some(axis_pair) = input.clamped_axis_pair(PlayerAction::Run) {
+    for (player_entity, input, mut controls, launched, aiming) in query.iter_mut() {
+        if input.pressed(PlayerAction::AimLock) {
+            controls.desired_velocity = Vec3::ZERO;
+        } else if let Some(axis_pair) = input.clamped_axis_pair(PlayerAction::Run) {
             controls.desired_velocity = Vec3::X * axis_pair.x();
             if 0.1 < axis_pair.x().abs() {
                 controls.desired_forward = Vec3::X * axis_pair.x().signum();
@@ -76,13 +278,88 @@ fn apply_controls(
         } else {
             controls.desired_velocity = Vec3::ZERO;
         };
-        controls.jump = Some(input.clamped_value(PlayerAction::Jump)).filter(|jump| 0.0 < *jump);
+        if let Some(aim_direction) = aiming.direction {
+            if 0.1 < aim_direction.x.abs() {
+                controls.desired_forward = Vec3::X * aim_direction.x.signum();
+            }
+        }
+        controls.jump = if let Some(launched) = launched {
+            Some(launched.jump_boost)
+        } else {
+            Some(input.clamped_value(PlayerAction::Jump)).filter(|jump| 0.0 < *jump)
+        };
 
-        if input.just_pressed(PlayerAction::Shoot) {
+        // Shots are fired on release, so that holding the action can charge them.
+        if input.just_released(PlayerAction::Shoot) {
             shoot_events_writer.send(ShootEvent {
                 shooter_entity: player_entity,
-                direction: controls.desired_forward,
+                direction: aiming
+                    .direction
+                    .map_or(controls.desired_forward, |direction| direction.extend(0.0)),
+                charged: 1.0 <= shot_charge(input),
+            });
+        }
+
+        if input.just_pressed(PlayerAction::Harvest) {
+            harvest_events_writer.send(HarvestEvent {
+                harvester_entity: player_entity,
+            });
+        }
+
+        if input.just_pressed(PlayerAction::CycleAmmunition) {
+            cycle_ammunition_events_writer.send(CycleAmmunitionEvent {
+                carrier_entity: player_entity,
+            });
+        }
+
+        if input.just_pressed(PlayerAction::DropAmmunition) {
+            drop_ammunition_events_writer.send(DropAmmunitionEvent {
+                carrier_entity: player_entity,
+                direction: aiming
+                    .direction
+                    .filter(|_| aiming.held)
+                    .map_or(controls.desired_forward, |direction| direction.extend(0.0)),
+                thrown: aiming.held,
             });
         }
     }
 }
+
+const CLIMBING_SPEED: f32 = 6.0;
+
+fn apply_climbing(
+    rapier_context: Res<RapierContext>,
+    mut query: Query<(
+        Entity,
+        &ActionState<PlayerAction>,
+        &mut Velocity,
+        &mut GravityScale,
+    )>,
+    climbable_query: Query<(), With<Climbable>>,
+) {
+    for (player_entity, input, mut velocity, mut gravity_scale) in query.iter_mut() {
+        let touching_climbable =
+            rapier_context
+                .intersections_with(player_entity)
+                .any(|(e1, e2, intersecting)| {
+                    let other = if e1 == player_entity { e2 } else { e1 };
+                    intersecting && climbable_query.contains(other)
+                });
+        let climb_axis = input
+            .clamped_axis_pair(PlayerAction::Run)
+            .map(|axis_pair| axis_pair.y())
+            .filter(|y| 0.1 < y.abs());
+        let is_climbing = gravity_scale.0 == 0.0;
+        if !touching_climbable || input.just_pressed(PlayerAction::Jump) {
+            if is_climbing {
+                gravity_scale.0 = 1.0;
+            }
+        } else if let Some(climb_axis) = climb_axis {
+            gravity_scale.0 = 0.0;
+            velocity.linvel.y = CLIMBING_SPEED * climb_axis;
+        } else if is_climbing {
+            // Hold on to the vine
+            velocity.linvel.y = 0.0;
+        }
+    }
+}
@@ -1,8 +1,20 @@
+use std::time::Duration;
+
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use bevy_egui::egui;
 use bevy_rapier2d::prelude::*;
 use bevy_yoleck::prelude::*;
+use bevy_yoleck::vpeol::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::ammunition::{CanCarry, UseUpShotEvent};
+use crate::ammunition::{CanCarry, PickEvent, Pickable, UseUpShotEvent};
+use crate::animating::RotateAroundScaledAxis;
+use crate::editing_helpers::SnapToGrid;
+use crate::killing::{DamageEvent, Health};
+use crate::plant_definitions::{BulletDefinition, PlantDefinitions};
+use crate::planting::PlantType;
 use crate::player_controls::ShootEvent;
 use crate::utils::sensor_events_both_ways;
 use crate::AppState;
@@ -11,18 +23,102 @@ pub struct ShootingPlugin;
 
 impl Plugin for ShootingPlugin {
     fn build(&self, app: &mut App) {
+        app.add_yoleck_entity_type({
+            YoleckEntityType::new("WeaponPowerUp")
+                .with::<Vpeol3dPosition>()
+                .with::<WeaponStats>()
+                .with::<WeaponPowerUp>()
+                .insert_on_init_during_editor(|| SnapToGrid)
+                .insert_on_init(|| Pickable)
+        });
+        app.add_yoleck_edit_system(edit_weapon_stats);
+        app.add_yoleck_edit_system(edit_weapon_power_up);
+        app.yoleck_populate_schedule_mut()
+            .add_system(populate_weapon_power_up);
         app.add_systems(
             (update_can_shoot_cooldown, destroy_bullets_after_timeout)
                 .in_set(OnUpdate(AppState::Game)),
         );
+        app.add_system(pick_weapon_power_ups);
         app.add_system(apply_shooting);
         app.add_system(destroy_bullet_when_colliding_with_wall);
+        app.init_resource::<BulletPooling>();
+    }
+}
+
+/// Measures the bullet pool. Add it along with Bevy's diagnostics plugins.
+pub struct BulletDiagnosticsPlugin;
+
+impl Plugin for BulletDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_bullet_diagnostics);
+        app.add_system(measure_bullet_diagnostics);
     }
 }
 
+#[derive(YoleckComponent, Clone, PartialEq, Component, Serialize, Deserialize)]
+pub struct WeaponStats {
+    /// Shots per second.
+    pub fire_rate: f32,
+    pub bullet_speed_factor: f32,
+    pub bullet_lifetime: f32,
+    /// Where bullets spawn - `x` is the distance in the shooting direction and `y` is the height
+    /// above the shooter's center.
+    pub spawn_offset: Vec2,
+}
+
+/// Level files are not bound by the editor's slider, and a zero fire rate would mean an infinite
+/// cooldown.
+const MIN_FIRE_RATE: f32 = 0.2;
+
+impl Default for WeaponStats {
+    fn default() -> Self {
+        Self {
+            fire_rate: 1.0,
+            bullet_speed_factor: 1.0,
+            // This should ensure out-of-screen bullets don't live forever.
+            bullet_lifetime: 20.0,
+            spawn_offset: Vec2::new(1.0, 0.4),
+        }
+    }
+}
+
+fn edit_weapon_stats(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut WeaponStats>) {
+    let Ok(mut stats) = edit.get_single_mut() else { return };
+    ui.add(
+        egui::Slider::new(&mut stats.fire_rate, MIN_FIRE_RATE..=10.0)
+            .prefix("Fire Rate: ")
+            .suffix(" shots/s"),
+    );
+    ui.add(
+        egui::Slider::new(&mut stats.bullet_speed_factor, 0.25..=4.0)
+            .prefix("Bullet Speed: ")
+            .suffix("x"),
+    );
+    ui.add(
+        egui::Slider::new(&mut stats.bullet_lifetime, 0.5..=30.0)
+            .prefix("Bullet Lifetime: ")
+            .suffix(" seconds"),
+    );
+    ui.horizontal(|ui| {
+        ui.label("Bullet Spawn Offset:");
+        ui.add(
+            egui::DragValue::new(&mut stats.spawn_offset.x)
+                .speed(0.05)
+                .prefix("forward: "),
+        );
+        ui.add(
+            egui::DragValue::new(&mut stats.spawn_offset.y)
+                .speed(0.05)
+                .prefix("up: "),
+        );
+    });
+}
+
 #[derive(Component)]
 pub struct CanShoot {
     cooldown: Timer,
+    power_up: Option<(WeaponStats, Timer)>,
 }
 
 impl Default for CanShoot {
@@ -34,90 +130,375 @@ impl Default for CanShoot {
                 timer.tick(timer.duration());
                 timer
             },
+            power_up: None,
         }
     }
 }
 
+impl CanShoot {
+    pub fn current_stats<'a>(&'a self, base_stats: &'a WeaponStats) -> &'a WeaponStats {
+        if let Some((stats, _)) = &self.power_up {
+            stats
+        } else {
+            base_stats
+        }
+    }
+
+    /// How much of the cooldown since the last shot has passed. `1.0` means ready to shoot.
+    pub fn cooldown_progress(&self) -> f32 {
+        self.cooldown.percent()
+    }
+}
+
 fn update_can_shoot_cooldown(time: Res<Time>, mut query: Query<&mut CanShoot>) {
     for mut can_shoot in query.iter_mut() {
         can_shoot.cooldown.tick(time.delta());
+        if let Some((_, power_up_timer)) = &mut can_shoot.power_up {
+            if power_up_timer.tick(time.delta()).finished() {
+                can_shoot.power_up = None;
+            }
+        }
+    }
+}
+
+#[derive(YoleckComponent, Clone, PartialEq, Component, Serialize, Deserialize)]
+pub struct WeaponPowerUp {
+    pub duration: f32,
+}
+
+impl Default for WeaponPowerUp {
+    fn default() -> Self {
+        Self { duration: 10.0 }
+    }
+}
+
+fn edit_weapon_power_up(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut WeaponPowerUp>) {
+    let Ok(mut power_up) = edit.get_single_mut() else { return };
+    ui.add(
+        egui::Slider::new(&mut power_up.duration, 1.0..=60.0)
+            .prefix("Power-Up Duration: ")
+            .suffix(" seconds"),
+    );
+}
+
+fn populate_weapon_power_up(
+    mut populate: YoleckPopulate<(), With<WeaponPowerUp>>,
+    asset_server: Res<AssetServer>,
+) {
+    populate.populate(|ctx, mut cmd, ()| {
+        if ctx.is_first_time() {
+            cmd.insert(VpeolWillContainClickableChildren);
+            cmd.insert(VisibilityBundle::default());
+            cmd.with_children(|commands| {
+                let mut child = commands.spawn(SceneBundle {
+                    scene: asset_server.load("Bullet.glb#Scene0"),
+                    transform: Transform::from_scale(Vec3::ONE * 3.0),
+                    ..Default::default()
+                });
+                child.insert(RotateAroundScaledAxis(2.0 * Vec3::Y));
+            });
+            cmd.insert(RigidBody::Fixed);
+            cmd.insert(Collider::ball(0.6));
+            cmd.insert(Sensor);
+            cmd.insert(ActiveEvents::COLLISION_EVENTS);
+        }
+    });
+}
+
+fn pick_weapon_power_ups(
+    mut reader: EventReader<PickEvent>,
+    mut can_shoot_query: Query<&mut CanShoot>,
+    power_up_query: Query<(&WeaponStats, &WeaponPowerUp)>,
+    mut commands: Commands,
+) {
+    for event in reader.iter() {
+        let (Ok(mut can_shoot), Ok((stats, power_up))) = (
+            can_shoot_query.get_mut(event.picker),
+            power_up_query.get(event.pickable),
+        ) else { continue };
+        can_shoot.power_up = Some((
+            stats.clone(),
+            Timer::from_seconds(power_up.duration, TimerMode::Once),
+        ));
+        commands.entity(event.pickable).despawn_recursive();
     }
 }
 
+const CHARGED_SHOT_COST: usize = 2;
+
+const CHARGED_SHOT_SPEED_FACTOR: f32 = 1.75;
+
+/// Bullets pass through their own faction.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Faction {
+    Player,
+    Goblins,
+}
+
+#[derive(Component)]
+pub struct InnateAmmunition(pub BulletDefinition);
+
 #[derive(Component)]
 pub struct Bullet {
+    pub faction: Faction,
+    damage: u32,
     timeout: Timer,
+    pierces: bool,
+    ricochets_left: usize,
 }
 
 #[derive(Component)]
 pub struct DestroysBullets;
 
+#[derive(Component)]
+pub struct DestroysBulletsOf(pub Faction);
+
+/// Bullets are not despawned when they hit something - they are hidden and disabled, and reused
+/// for the next shots.
+#[derive(Component)]
+struct InactiveBullet;
+
+/// Whether bullets are returned to the pool. Turning it off despawns them instead, which is only
+/// useful for measuring what the pool saves.
+#[derive(Resource)]
+pub struct BulletPooling(pub bool);
+
+impl Default for BulletPooling {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+fn deactivate_bullet(mut cmd: EntityCommands, pooling: &BulletPooling) {
+    if !pooling.0 {
+        cmd.despawn_recursive();
+        return;
+    }
+    cmd.insert((
+        InactiveBullet,
+        RigidBodyDisabled,
+        ColliderDisabled,
+        Visibility::Hidden,
+        Velocity::zero(),
+    ));
+}
+
+pub const BULLETS_ACTIVE: DiagnosticId =
+    DiagnosticId::from_u128(301561186850639821786233316398789299462);
+pub const BULLETS_POOLED: DiagnosticId =
+    DiagnosticId::from_u128(212476082122991241527912884338728434332);
+pub const BULLET_ENTITIES_SPAWNED: DiagnosticId =
+    DiagnosticId::from_u128(157098549734061513060593176555228133617);
+
+fn setup_bullet_diagnostics(mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics.add(Diagnostic::new(BULLETS_ACTIVE, "bullets_active", 20));
+    diagnostics.add(Diagnostic::new(BULLETS_POOLED, "bullets_pooled", 20));
+    diagnostics.add(Diagnostic::new(
+        BULLET_ENTITIES_SPAWNED,
+        "bullet_entities_spawned",
+        20,
+    ));
+}
+
+fn measure_bullet_diagnostics(
+    mut diagnostics: ResMut<Diagnostics>,
+    active_query: Query<(), (With<Bullet>, Without<InactiveBullet>)>,
+    pooled_query: Query<(), With<InactiveBullet>>,
+    spawned_query: Query<(), Added<Bullet>>,
+) {
+    diagnostics.add_measurement(BULLETS_ACTIVE, || active_query.iter().count() as f64);
+    diagnostics.add_measurement(BULLETS_POOLED, || pooled_query.iter().count() as f64);
+    diagnostics.add_measurement(BULLET_ENTITIES_SPAWNED, || {
+        spawned_query.iter().count() as f64
+    });
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn apply_shooting(
     mut reader: EventReader<ShootEvent>,
-    mut shooter_query: Query<(&mut CanShoot, &CanCarry, &GlobalTransform)>,
+    mut shooter_query: Query<(
+        &mut CanShoot,
+        &WeaponStats,
+        Option<&CanCarry>,
+        Option<&InnateAmmunition>,
+        &Faction,
+        &GlobalTransform,
+    )>,
+    plant_type_query: Query<&PlantType>,
+    inactive_bullets_query: Query<Entity, With<InactiveBullet>>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    plant_definitions: Res<PlantDefinitions>,
     mut use_up_shots_writer: EventWriter<UseUpShotEvent>,
 ) {
+    let mut inactive_bullets = inactive_bullets_query.iter();
     for event in reader.iter() {
-        let Ok((mut can_shoot, can_carry, shooter_transform)) = shooter_query.get_mut(event.shooter_entity) else { continue };
+        let Ok((mut can_shoot, base_stats, can_carry, innate_ammunition, faction, shooter_transform)) = shooter_query.get_mut(event.shooter_entity) else { continue };
         if !can_shoot.cooldown.finished() {
             continue;
         }
-        let carried_ammunition_entity = if let Some(entity) = can_carry.carries {
-            entity
+        let carried_ammunition_entity = can_carry.and_then(CanCarry::carries);
+        let profile = if let Some(carried_ammunition_entity) = carried_ammunition_entity {
+            let Ok(plant_type) = plant_type_query.get(carried_ammunition_entity) else { continue };
+            &plant_definitions.get(plant_type).bullet
+        } else if let Some(InnateAmmunition(profile)) = innate_ammunition {
+            profile
         } else {
             continue;
         };
+        let stats = can_shoot.current_stats(base_stats).clone();
+        can_shoot.cooldown.set_duration(Duration::from_secs_f32(
+            1.0 / stats.fire_rate.max(MIN_FIRE_RATE),
+        ));
         can_shoot.cooldown.reset();
-        let mut cmd = commands.spawn_empty();
-        cmd.insert(SceneBundle {
-            scene: asset_server.load("Bullet.glb#Scene0"),
-            transform: Transform::from_translation(
-                shooter_transform.translation() + event.direction + 0.4 * Vec3::Y,
-            ),
-            ..Default::default()
-        });
-        cmd.insert(RigidBody::Dynamic);
-        cmd.insert(GravityScale(0.0));
-        cmd.insert(Velocity::linear(20.0 * event.direction.truncate()));
-        cmd.insert(Collider::ball(0.2));
-        cmd.insert(Sensor);
-        cmd.insert(Bullet {
-            // This should ensure out-of-screen bullets don't live forever.
-            timeout: Timer::from_seconds(20.0, TimerMode::Once),
-        });
-        cmd.insert(ActiveEvents::COLLISION_EVENTS);
-        cmd.insert(YoleckBelongsToLevel);
-        use_up_shots_writer.send(UseUpShotEvent {
-            carrier_entity: event.shooter_entity,
-            carried_ammunition_entity,
-            ejecet_direction: -event.direction,
-        });
+        let (speed, scale, shots) = if event.charged {
+            (CHARGED_SHOT_SPEED_FACTOR, 1.6, CHARGED_SHOT_COST)
+        } else {
+            (1.0, 1.0, 1)
+        };
+        let speed = speed * stats.bullet_speed_factor * profile.speed;
+        let spread = profile.spread_degrees.to_radians();
+        for i in 0..profile.count {
+            let angle = if profile.count <= 1 {
+                0.0
+            } else {
+                spread * (i as f32 / (profile.count - 1) as f32 - 0.5)
+            };
+            let direction = Quat::from_rotation_z(angle) * event.direction;
+            let transform = Transform::from_translation(
+                shooter_transform.translation()
+                    + stats.spawn_offset.x * direction
+                    + stats.spawn_offset.y * Vec3::Y,
+            )
+            .with_scale(Vec3::ONE * scale);
+            let mut cmd = if let Some(bullet_entity) = inactive_bullets.next() {
+                let mut cmd = commands.entity(bullet_entity);
+                cmd.remove::<(InactiveBullet, RigidBodyDisabled, ColliderDisabled)>();
+                cmd.insert(Visibility::Inherited);
+                cmd.insert(transform);
+                cmd
+            } else {
+                let mut cmd = commands.spawn(SceneBundle {
+                    scene: asset_server.load("Bullet.glb#Scene0"),
+                    transform,
+                    ..Default::default()
+                });
+                cmd.insert(RigidBody::Dynamic);
+                cmd.insert(Collider::ball(0.2));
+                cmd.insert(Sensor);
+                cmd.insert(ActiveEvents::COLLISION_EVENTS);
+                cmd.insert(YoleckBelongsToLevel);
+                cmd
+            };
+            cmd.insert(GravityScale(profile.gravity));
+            cmd.insert(Velocity::linear(speed * direction.truncate()));
+            cmd.insert(Bullet {
+                faction: *faction,
+                damage: shots as u32,
+                timeout: Timer::from_seconds(stats.bullet_lifetime, TimerMode::Once),
+                pierces: profile.pierces || event.charged,
+                ricochets_left: profile.ricochets,
+            });
+        }
+        if let Some(carried_ammunition_entity) = carried_ammunition_entity {
+            use_up_shots_writer.send(UseUpShotEvent {
+                carrier_entity: event.shooter_entity,
+                carried_ammunition_entity,
+                ejecet_direction: -event.direction,
+                shots,
+            });
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn destroy_bullet_when_colliding_with_wall(
     mut reader: EventReader<CollisionEvent>,
-    bullets_query: Query<(), With<Bullet>>,
+    mut bullets_query: Query<
+        (&mut Bullet, &GlobalTransform, &mut Velocity),
+        Without<InactiveBullet>,
+    >,
     destroy_bullets_query: Query<(), With<DestroysBullets>>,
+    destroy_bullets_of_query: Query<&DestroysBulletsOf>,
+    factions_query: Query<&Faction>,
+    healths_query: Query<&Health>,
+    rapier_context: Res<RapierContext>,
+    pooling: Res<BulletPooling>,
     mut commands: Commands,
+    mut damage_events_writer: EventWriter<DamageEvent>,
 ) {
     for (e1, e2) in sensor_events_both_ways(&mut reader) {
-        if bullets_query.contains(e1) && destroy_bullets_query.contains(e2) {
-            commands.entity(e1).despawn_recursive();
+        let Ok((mut bullet, transform, mut velocity)) = bullets_query.get_mut(e1) else { continue };
+        if factions_query
+            .get(e2)
+            .is_ok_and(|faction| *faction == bullet.faction)
+        {
+            continue;
+        }
+        let destroys_bullet = destroy_bullets_query.contains(e2)
+            || destroy_bullets_of_query
+                .get(e2)
+                .is_ok_and(|DestroysBulletsOf(faction)| *faction == bullet.faction);
+        if !destroys_bullet {
+            continue;
         }
+        let is_killable = if let Ok(health) = healths_query.get(e2) {
+            if health.is_alive() {
+                damage_events_writer.send(DamageEvent {
+                    entity_to_damage: e2,
+                    amount: bullet.damage,
+                    source: e1,
+                });
+            }
+            true
+        } else {
+            false
+        };
+        if bullet.pierces && is_killable {
+            continue;
+        }
+        if 0 < bullet.ricochets_left && !is_killable {
+            if let Some(normal) = wall_normal(&rapier_context, transform, &velocity, e2) {
+                bullet.ricochets_left -= 1;
+                let speed_into_wall = velocity.linvel.dot(normal);
+                velocity.linvel -= 2.0 * speed_into_wall * normal;
+                continue;
+            }
+        }
+        deactivate_bullet(commands.entity(e1), &pooling);
     }
 }
 
+/// Bullets are sensors, so there is no contact normal - instead this casts a ray along the
+/// bullet's path back into the wall.
+fn wall_normal(
+    rapier_context: &RapierContext,
+    transform: &GlobalTransform,
+    velocity: &Velocity,
+    wall: Entity,
+) -> Option<Vec2> {
+    let direction = velocity.linvel.try_normalize()?;
+    let is_wall = |entity: Entity| entity == wall;
+    let (_, intersection) = rapier_context.cast_ray_and_get_normal(
+        transform.translation().truncate() - direction,
+        direction,
+        2.0,
+        true,
+        QueryFilter::new().predicate(&is_wall),
+    )?;
+    // The normal is zero when the ray starts inside the wall.
+    Some(intersection.normal).filter(|normal| *normal != Vec2::ZERO)
+}
+
 fn destroy_bullets_after_timeout(
     time: Res<Time>,
-    mut query: Query<(Entity, &mut Bullet)>,
+    mut query: Query<(Entity, &mut Bullet), Without<InactiveBullet>>,
+    pooling: Res<BulletPooling>,
     mut commands: Commands,
 ) {
     for (bullet_entity, mut bullet) in query.iter_mut() {
         if bullet.timeout.tick(time.delta()).finished() {
-            commands.entity(bullet_entity).despawn_recursive();
+            deactivate_bullet(commands.entity(bullet_entity), &pooling);
         }
     }
 }